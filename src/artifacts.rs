@@ -0,0 +1,224 @@
+use crate::i18n::I18n;
+use crate::storage::{self, CommandExecution, CommandRecord, TrackedArtifacts, TrackedFile};
+use anyhow::{anyhow, Context, Result};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Copy the files matching `specs` (resolved against `working_dir`) into a new
+/// snapshot directory, inside `staging_dir` when given. File N of the returned
+/// list is stored in the snapshot as `N`.
+pub fn snapshot(
+    specs: &[String],
+    working_dir: &Path,
+    staging_dir: Option<&Path>,
+    i18n: &I18n,
+) -> Result<(TrackedArtifacts, tempfile::TempDir)> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix("artifacts_");
+    let dir = match staging_dir {
+        Some(staging) => fs::create_dir_all(staging).and_then(|_| builder.tempdir_in(staging)),
+        None => builder.tempdir(),
+    }
+    .context(i18n.t("error_snapshot_artifacts"))?;
+
+    let mut files = Vec::new();
+    for (index, path) in resolve(specs, working_dir, i18n)?.into_iter().enumerate() {
+        // Copied and hashed in chunks: tracked files can be large build outputs.
+        let copy = dir.path().join(index.to_string());
+        let size = fs::copy(working_dir.join(&path), &copy).with_context(|| {
+            i18n.t_format("error_read_tracked_file", &[&path.display().to_string()])
+        })?;
+        let sha256 = storage::hash_file(&copy).context(i18n.t("error_snapshot_artifacts"))?;
+        files.push(TrackedFile { path, size, sha256 });
+    }
+    Ok((
+        TrackedArtifacts {
+            specs: specs.to_vec(),
+            files,
+        },
+        dir,
+    ))
+}
+
+/// Files matched by `specs`, sorted and relative to `working_dir` where possible.
+/// Directories are tracked recursively; paths that do not exist match nothing.
+fn resolve(specs: &[String], working_dir: &Path, i18n: &I18n) -> Result<Vec<PathBuf>> {
+    let mut found = BTreeSet::new();
+    for spec in specs {
+        if spec.contains(['*', '?', '[']) {
+            // Escape the working directory so only the user's pattern is special.
+            let pattern = if Path::new(spec).is_absolute() {
+                spec.clone()
+            } else {
+                format!(
+                    "{}{}{}",
+                    glob::Pattern::escape(&working_dir.to_string_lossy()),
+                    std::path::MAIN_SEPARATOR,
+                    spec
+                )
+            };
+            let matches = glob::glob(&pattern).map_err(|e| {
+                anyhow!(i18n.t_format("track_bad_glob", &[spec.as_str(), &e.to_string()]))
+            })?;
+            found.extend(matches.flatten().filter(|path| path.is_file()));
+        } else {
+            let path = working_dir.join(spec);
+            if path.is_dir() {
+                walk(&path, &mut found)?;
+            } else if path.is_file() {
+                found.insert(path);
+            }
+        }
+    }
+    Ok(found
+        .into_iter()
+        .map(|path| match path.strip_prefix(working_dir) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => path,
+        })
+        .collect())
+}
+
+/// Collect the files under `dir`. Symlinks are not followed into directories,
+/// so a link back up the tree cannot make the walk loop; links to files are
+/// tracked like the files themselves.
+fn walk(dir: &Path, found: &mut BTreeSet<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            walk(&path, found)?;
+        } else if file_type.is_file() || (file_type.is_symlink() && path.is_file()) {
+            found.insert(path);
+        }
+    }
+    Ok(())
+}
+
+/// A tracked file that differs between two runs, with its index in each run's list.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FileChange<'a> {
+    pub path: &'a Path,
+    pub before: Option<usize>,
+    pub after: Option<usize>,
+}
+
+/// Tracked files added, removed or modified between two runs, plus the number
+/// left unchanged. `None` unless both runs tracked files.
+pub fn changed_files<'a>(
+    earlier: &'a CommandRecord,
+    later: &'a CommandRecord,
+) -> Option<(Vec<FileChange<'a>>, usize)> {
+    let before = &earlier.artifacts.as_ref()?.files;
+    let after = &later.artifacts.as_ref()?.files;
+    let paths: BTreeSet<&Path> = before
+        .iter()
+        .chain(after.iter())
+        .map(|file| file.path.as_path())
+        .collect();
+
+    let mut changes = Vec::new();
+    let mut unchanged = 0;
+    for path in paths {
+        let old = before.iter().position(|file| file.path == path);
+        let new = after.iter().position(|file| file.path == path);
+        match (old, new) {
+            (Some(o), Some(n)) if before[o].sha256 == after[n].sha256 => unchanged += 1,
+            _ => changes.push(FileChange {
+                path,
+                before: old,
+                after: new,
+            }),
+        }
+    }
+    Some((changes, unchanged))
+}
+
+/// Text of tracked file `index` for diffing; `None` for binary or missing snapshots.
+pub fn file_text(execution: &CommandExecution, index: usize) -> Option<String> {
+    let bytes = execution.artifact_bytes(index)?;
+    if bytes.contains(&0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_resolves_files_dirs_and_globs() {
+        let i18n = I18n::new("en");
+        let work = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(work.path().join("out/nested")).unwrap();
+        fs::write(work.path().join("out/nested/b.txt"), "b").unwrap();
+        fs::write(work.path().join("report.json"), "{}").unwrap();
+        fs::write(work.path().join("skip.log"), "x").unwrap();
+
+        let specs = vec![
+            "out".to_string(),
+            "*.json".to_string(),
+            "missing.txt".to_string(),
+        ];
+        let (tracked, dir) = snapshot(&specs, work.path(), None, &i18n).unwrap();
+        let paths: Vec<&Path> = tracked.files.iter().map(|f| f.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            vec![Path::new("out/nested/b.txt"), Path::new("report.json")]
+        );
+        assert_eq!(fs::read(dir.path().join("1")).unwrap(), b"{}");
+        assert_eq!(tracked.files[0].size, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn snapshot_does_not_follow_symlinked_directories() {
+        let i18n = I18n::new("en");
+        let work = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(work.path().join("d")).unwrap();
+        fs::write(work.path().join("d/f.txt"), "f").unwrap();
+        std::os::unix::fs::symlink("..", work.path().join("d/up")).unwrap();
+        std::os::unix::fs::symlink("f.txt", work.path().join("d/link.txt")).unwrap();
+
+        let (tracked, _dir) = snapshot(&["d".to_string()], work.path(), None, &i18n).unwrap();
+        let paths: Vec<&Path> = tracked.files.iter().map(|f| f.path.as_path()).collect();
+        assert_eq!(paths, vec![Path::new("d/f.txt"), Path::new("d/link.txt")]);
+        assert_eq!(tracked.files[0].sha256, tracked.files[1].sha256);
+    }
+
+    #[test]
+    fn changed_files_reports_added_removed_and_modified() {
+        let file = |path: &str, sha: &str| TrackedFile {
+            path: PathBuf::from(path),
+            size: 1,
+            sha256: sha.to_string(),
+        };
+        let record = |files: Vec<TrackedFile>| CommandRecord {
+            artifacts: Some(TrackedArtifacts {
+                specs: vec![".".to_string()],
+                files,
+            }),
+            ..CommandRecord::for_test("make")
+        };
+        let earlier = record(vec![file("a", "1"), file("b", "1"), file("c", "1")]);
+        let later = record(vec![file("a", "1"), file("b", "2"), file("d", "1")]);
+
+        let (changes, unchanged) = changed_files(&earlier, &later).unwrap();
+        assert_eq!(unchanged, 1);
+        let summary: Vec<(&Path, Option<usize>, Option<usize>)> = changes
+            .iter()
+            .map(|c| (c.path, c.before, c.after))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Path::new("b"), Some(1), Some(1)),
+                (Path::new("c"), Some(2), None),
+                (Path::new("d"), None, Some(2)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use crate::differ::Differ;
+use crate::executor::{CommandExecutor, ExecOptions, StdinSource};
+use crate::i18n::I18n;
+use crate::storage::{CommandExecution, TerminationReason};
+use crate::store_manager::StoreManager;
+use anyhow::{anyhow, Result};
+use colored::*;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// One command from a batch file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BatchEntry {
+    /// Label shown in the summary instead of the command
+    #[serde(default)]
+    pub name: Option<String>,
+    pub command: String,
+}
+
+impl BatchEntry {
+    fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.command)
+    }
+}
+
+#[derive(Deserialize)]
+struct BatchFile {
+    #[serde(default)]
+    commands: Vec<BatchEntry>,
+}
+
+/// How a batch run compares with the previous run of the same command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// No earlier run was recorded
+    New,
+    Unchanged,
+    Changed {
+        /// Previous and current exit code, when they differ
+        exit: Option<(i32, i32)>,
+        output: bool,
+    },
+}
+
+/// Result of one batch entry: the saved run and its change, or why it could not run.
+pub type BatchResult = Result<(CommandExecution, Change), String>;
+
+/// Read a batch file. `.toml` files hold a `[[commands]]` list with `command`
+/// and an optional `name`; anything else is one command per line, with blank
+/// lines and `#` comments skipped.
+pub fn parse_batch_file(path: &Path, i18n: &I18n) -> Result<Vec<BatchEntry>> {
+    let display = path.display().to_string();
+    let text = fs::read_to_string(path)
+        .map_err(|e| anyhow!(i18n.t_format("batch_file_read", &[&display, &e.to_string()])))?;
+    let entries = if path.extension().is_some_and(|ext| ext == "toml") {
+        let file: BatchFile = toml::from_str(&text)
+            .map_err(|e| anyhow!(i18n.t_format("batch_bad_toml", &[&display, &e.to_string()])))?;
+        file.commands
+    } else {
+        parse_lines(&text)
+    };
+    if entries.is_empty() {
+        return Err(anyhow!(i18n.t_format("batch_empty", &[&display])));
+    }
+    Ok(entries)
+}
+
+fn parse_lines(text: &str) -> Vec<BatchEntry> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| BatchEntry {
+            name: None,
+            command: line.to_string(),
+        })
+        .collect()
+}
+
+/// Run every entry with at most `jobs` commands at once. Each run is compared
+/// with the last recorded run of its command and saved; saves are serialized
+/// so short codes and the index stay consistent. Results keep file order.
+pub fn run_batch(
+    entries: &[BatchEntry],
+    exec_options: &ExecOptions,
+    jobs: usize,
+    store: &StoreManager,
+    i18n: &I18n,
+) -> Vec<BatchResult> {
+    let options = ExecOptions {
+        quiet: true,
+        stdin: StdinSource::Null,
+        ..exec_options.clone()
+    };
+    let total = entries.len();
+    let next = AtomicUsize::new(0);
+    let finished = Mutex::new((0usize, vec![None; total]));
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, total.max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                if index >= total {
+                    break;
+                }
+                let entry = &entries[index];
+                let executed = CommandExecutor::execute(&entry.command, &options, i18n);
+
+                let mut guard = finished.lock().unwrap_or_else(|e| e.into_inner());
+                let result = executed
+                    .and_then(|execution| save_and_compare(execution, store, i18n))
+                    .map_err(|e| e.to_string());
+                guard.0 += 1;
+                println!("{}", progress_line(guard.0, total, entry, &result, i18n));
+                guard.1[index] = Some(result);
+            });
+        }
+    });
+
+    let (_, results) = finished.into_inner().unwrap_or_else(|e| e.into_inner());
+    results
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| Err(String::new())))
+        .collect()
+}
+
+fn save_and_compare(
+    mut execution: CommandExecution,
+    store: &StoreManager,
+    i18n: &I18n,
+) -> Result<(CommandExecution, Change)> {
+    let previous = store.get_target_record(
+        &execution.record.command_hash,
+        crate::DiffTarget::Last,
+        &execution.record.record_id,
+    )?;
+    store.assign_short_code(&mut execution.record, i18n)?;
+    store.save_execution(&mut execution, i18n)?;
+    let change = match previous {
+        None => Change::New,
+        Some(previous) => {
+            let (before, after) = (previous.record.exit_code, execution.record.exit_code);
+            let exit = (before != after).then_some((before, after));
+            let output = Differ::outputs_differ(&previous, &execution);
+            if exit.is_none() && !output {
+                Change::Unchanged
+            } else {
+                Change::Changed { exit, output }
+            }
+        }
+    };
+    Ok((execution, change))
+}
+
+fn failed(execution: &CommandExecution) -> bool {
+    execution.record.exit_code != 0 || execution.record.termination != TerminationReason::Completed
+}
+
+/// Whether any entry failed to start or finished unsuccessfully.
+pub fn any_failed(results: &[BatchResult]) -> bool {
+    results.iter().any(|r| match r {
+        Ok((execution, _)) => failed(execution),
+        Err(_) => true,
+    })
+}
+
+fn change_label(change: &Change, i18n: &I18n) -> String {
+    match change {
+        Change::New => i18n.t("batch_change_new"),
+        Change::Unchanged => i18n.t("batch_change_unchanged"),
+        Change::Changed { exit, output } => {
+            let mut parts = Vec::new();
+            if let Some((before, after)) = exit {
+                parts.push(i18n.t_format(
+                    "batch_change_exit",
+                    &[&before.to_string(), &after.to_string()],
+                ));
+            }
+            if *output {
+                parts.push(i18n.t("batch_change_output"));
+            }
+            parts.join(", ")
+        }
+    }
+}
+
+fn progress_line(
+    done: usize,
+    total: usize,
+    entry: &BatchEntry,
+    result: &BatchResult,
+    i18n: &I18n,
+) -> String {
+    let counter = format!("[{}/{}]", done, total).dimmed();
+    match result {
+        Ok((execution, change)) => {
+            let line = i18n.t_format(
+                "batch_progress",
+                &[
+                    entry.label(),
+                    &execution.record.exit_code.to_string(),
+                    &execution.record.duration_ms.to_string(),
+                    &change_label(change, i18n),
+                ],
+            );
+            if failed(execution) {
+                format!("{} {}", counter, line.red())
+            } else {
+                format!("{} {}", counter, line)
+            }
+        }
+        Err(err) => format!(
+            "{} {}",
+            counter,
+            i18n.t_format("batch_failed_to_run", &[entry.label(), err])
+                .red()
+        ),
+    }
+}
+
+/// Table of every entry with its exit code, change against the previous run,
+/// short code and duration, followed by totals.
+pub fn summary_table(entries: &[BatchEntry], results: &[BatchResult], i18n: &I18n) -> String {
+    const MAX_LABEL: usize = 48;
+    let labels: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            let label = entry.label();
+            if label.chars().count() > MAX_LABEL {
+                let cut: String = label.chars().take(MAX_LABEL - 1).collect();
+                format!("{}…", cut)
+            } else {
+                label.to_string()
+            }
+        })
+        .collect();
+    let rows: Vec<[String; 4]> = results
+        .iter()
+        .map(|result| match result {
+            Ok((execution, change)) => [
+                execution.record.exit_code.to_string(),
+                change_label(change, i18n),
+                execution.record.short_code.clone().unwrap_or_default(),
+                format!("{}ms", execution.record.duration_ms),
+            ],
+            Err(_) => [
+                "-".to_string(),
+                i18n.t("batch_change_error"),
+                String::new(),
+                String::new(),
+            ],
+        })
+        .collect();
+
+    let headers = [
+        i18n.t("batch_col_command"),
+        i18n.t("batch_col_exit"),
+        i18n.t("batch_col_change"),
+        i18n.t("batch_col_code"),
+        i18n.t("batch_col_time"),
+    ];
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for (label, row) in labels.iter().zip(&rows) {
+        widths[0] = widths[0].max(label.chars().count());
+        for (column, cell) in row.iter().enumerate() {
+            widths[column + 1] = widths[column + 1].max(cell.chars().count());
+        }
+    }
+    let pad =
+        |text: &str, width: usize| format!("{}{}", text, " ".repeat(width - text.chars().count()));
+
+    let mut output = String::new();
+    let header: Vec<String> = headers
+        .iter()
+        .zip(&widths)
+        .map(|(h, &w)| pad(h, w))
+        .collect();
+    output.push_str(&format!("{}\n", header.join("  ").trim_end().bold()));
+
+    let (mut changed, mut new, mut unchanged) = (0, 0, 0);
+    for ((label, row), result) in labels.iter().zip(&rows).zip(results) {
+        let mut cells = vec![pad(label, widths[0])];
+        cells.extend(
+            row.iter()
+                .enumerate()
+                .map(|(c, cell)| pad(cell, widths[c + 1])),
+        );
+        let line = cells.join("  ").trim_end().to_string();
+        let line = match result {
+            Ok((_, Change::Unchanged)) => {
+                unchanged += 1;
+                line.normal()
+            }
+            Ok((_, Change::New)) => {
+                new += 1;
+                line.cyan()
+            }
+            Ok((_, Change::Changed { .. })) => {
+                changed += 1;
+                line.yellow()
+            }
+            Err(_) => line.red(),
+        };
+        output.push_str(&format!("{}\n", line));
+    }
+
+    let failures = results
+        .iter()
+        .filter(|r| r.as_ref().map_or(true, |(execution, _)| failed(execution)))
+        .count();
+    output.push('\n');
+    output.push_str(&format!(
+        "{}\n",
+        i18n.t_format(
+            "batch_totals",
+            &[
+                &results.len().to_string(),
+                &changed.to_string(),
+                &new.to_string(),
+                &unchanged.to_string(),
+                &failures.to_string(),
+            ],
+        )
+        .bold()
+    ));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_batch_file_skips_blanks_and_comments() {
+        let entries = parse_lines("# health checks\n\nuptime\n  df -h /  \n");
+        let commands: Vec<&str> = entries.iter().map(|e| e.command.as_str()).collect();
+        assert_eq!(commands, vec!["uptime", "df -h /"]);
+        assert!(entries.iter().all(|e| e.name.is_none()));
+    }
+
+    #[test]
+    fn toml_batch_file_keeps_names() {
+        let i18n = I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("checks.toml");
+        fs::write(
+            &path,
+            "[[commands]]\nname = \"disk\"\ncommand = \"df -h /\"\n\n[[commands]]\ncommand = \"uptime\"\n",
+        )
+        .unwrap();
+        let entries = parse_batch_file(&path, &i18n).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].label(), "disk");
+        assert_eq!(entries[1].label(), "uptime");
+    }
+}
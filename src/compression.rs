@@ -0,0 +1,73 @@
+use crate::storage::Compression;
+use std::io::{self, Read, Write};
+
+impl Compression {
+    /// Suffix of blob files stored with this codec.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst",
+        }
+    }
+
+    pub const ALL: [Compression; 3] = [Compression::None, Compression::Gzip, Compression::Zstd];
+}
+
+/// Compress everything `reader` yields into `writer`, returning the writer.
+pub fn compress_stream<W: Write>(
+    codec: Compression,
+    mut reader: impl Read,
+    mut writer: W,
+) -> io::Result<W> {
+    match codec {
+        Compression::None => {
+            io::copy(&mut reader, &mut writer)?;
+            Ok(writer)
+        }
+        Compression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            io::copy(&mut reader, &mut encoder)?;
+            encoder.finish()
+        }
+        Compression::Zstd => {
+            let mut encoder = zstd::stream::write::Encoder::new(writer, 0)?;
+            io::copy(&mut reader, &mut encoder)?;
+            encoder.finish()
+        }
+    }
+}
+
+/// Reader yielding the decompressed contents of `reader`.
+pub fn decompress_stream<'a>(
+    codec: Compression,
+    reader: impl Read + 'a,
+) -> io::Result<Box<dyn Read + 'a>> {
+    Ok(match codec {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(flate2::read::GzDecoder::new(reader)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(reader)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codecs_round_trip() {
+        let log = "step ok\n".repeat(1000).into_bytes();
+        for codec in Compression::ALL {
+            let stored = compress_stream(codec, log.as_slice(), Vec::new()).unwrap();
+            if codec != Compression::None {
+                assert!(stored.len() < log.len() / 10);
+            }
+            let mut read = Vec::new();
+            decompress_stream(codec, stored.as_slice())
+                .unwrap()
+                .read_to_end(&mut read)
+                .unwrap();
+            assert_eq!(read, log);
+        }
+    }
+}
@@ -7,12 +7,31 @@ use std::path::PathBuf;
 pub struct Config {
     pub storage: StorageConfig,
     pub display: DisplayConfig,
+    #[serde(default)]
+    pub run: RunConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StorageConfig {
     pub max_retention_days: u32,
     pub auto_archive: bool,
+    // Bytes kept per output stream; larger output keeps its first and last halves (0 = no limit)
+    #[serde(default = "default_max_output_bytes")]
+    pub max_output_bytes: u64,
+    // Codec for stored stdout/stderr: none, gzip or zstd
+    #[serde(default)]
+    pub compression: crate::storage::Compression,
+    // Outputs smaller than this many bytes are stored uncompressed
+    #[serde(default = "default_compression_threshold")]
+    pub compression_threshold: u64,
+}
+
+fn default_max_output_bytes() -> u64 {
+    64 * 1024 * 1024
+}
+
+fn default_compression_threshold() -> u64 {
+    64 * 1024
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -37,14 +56,66 @@ impl Default for DisplayConfig {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RunConfig {
+    // Capture output through a pseudo-terminal by default (same as `dt run --pty`)
+    pub pty: bool,
+    // Seconds to wait after SIGTERM before sending SIGKILL when `--timeout` fires
+    pub kill_grace_secs: u64,
+    // Environment variables captured with each run; `*` matches any run of characters
+    pub env_allowlist: Vec<String>,
+    // Variables never captured even when allowlisted (secrets)
+    pub env_denylist: Vec<String>,
+    // Shell used to run commands, with optional arguments (e.g. "bash -o pipefail")
+    pub shell: String,
+    // Commands `dt run-batch` runs at once unless `--jobs` is given
+    pub batch_jobs: usize,
+    // Record HEAD commit, branch and dirty state for runs inside a git repository
+    pub capture_git: bool,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            pty: false,
+            kill_grace_secs: 5,
+            env_allowlist: [
+                "PATH", "LANG", "LANGUAGE", "LC_*", "TZ", "TERM", "SHELL", "HOME", "USER",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            env_denylist: [
+                "*TOKEN*",
+                "*SECRET*",
+                "*PASSWORD*",
+                "*PASSWD*",
+                "*CREDENTIAL*",
+                "*_KEY",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            shell: "sh".to_string(),
+            batch_jobs: 4,
+            capture_git: true,
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             storage: StorageConfig {
                 max_retention_days: 365, // Default 1 year
                 auto_archive: true,
+                max_output_bytes: default_max_output_bytes(),
+                compression: Default::default(),
+                compression_threshold: default_compression_threshold(),
             },
             display: DisplayConfig::default(),
+            run: RunConfig::default(),
         }
     }
 }
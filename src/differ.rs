@@ -1,6 +1,9 @@
+use crate::artifacts::{self, FileChange};
 use crate::fuzzy_matcher::SkimMatcher;
 use crate::i18n::I18n;
-use crate::storage::CommandExecution;
+use crate::storage::{
+    CommandExecution, CommandRecord, GitInfo, OutputTruncation, ResourceUsage, TerminationReason,
+};
 use crate::store_manager::StoreManager;
 use anyhow::Result;
 use chrono::{DateTime, Datelike, Local};
@@ -22,6 +25,7 @@ use ratatui::{
 };
 use regex::Regex;
 use similar::{ChangeTag, TextDiff};
+use std::borrow::Cow;
 use std::io;
 use std::sync::OnceLock;
 use unicode_width::UnicodeWidthChar;
@@ -42,26 +46,13 @@ mod interactive_selection_tests {
     use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 
     fn exec_with(id: &str, ts: i64, command: &str) -> CommandExecution {
-        let timestamp = Utc.timestamp_opt(ts, 0).single().unwrap();
         let record = crate::storage::CommandRecord {
-            command: command.to_string(),
-            command_hash: crate::storage::hash_command(command),
-            timestamp,
-            working_dir: std::path::PathBuf::from("/tmp"),
-            exit_code: 0,
-            duration_ms: 1,
+            timestamp: Utc.timestamp_opt(ts, 0).single().unwrap(),
             record_id: id.to_string(),
             short_code: Some(id.to_string()),
+            ..crate::storage::CommandRecord::for_test(command)
         };
-        CommandExecution {
-            record,
-            stdout: format!("out-{}\n", id),
-            stderr: String::new(),
-            stdout_path: None,
-            stderr_path: None,
-            streamed_stdout: false,
-            streamed_stderr: false,
-        }
+        CommandExecution::for_test(record, &format!("out-{}\n", id))
     }
 
     fn apply_keys(
@@ -72,6 +63,7 @@ mod interactive_selection_tests {
     ) -> InteractiveSelectionStep {
         let mut loader = || Vec::<CommandExecution>::new();
         let mut delete_action: Option<fn(&CommandExecution) -> Result<()>> = None;
+        let mut rerun_action: Option<fn(&CommandExecution) -> Result<CommandExecution>> = None;
         let mut last = InteractiveSelectionStep::Continue {
             needs_redraw: false,
         };
@@ -84,6 +76,7 @@ mod interactive_selection_tests {
                 Some(24),
                 &mut loader,
                 &mut delete_action,
+                &mut rerun_action,
             );
             match step {
                 InteractiveSelectionStep::Continue { .. } => {
@@ -110,6 +103,7 @@ mod interactive_selection_tests {
             preview_offset: 0,
             show_help: false,
             focus: SelectionFocus::Selection,
+            preview_mode: PreviewMode::Split,
             pending_delete: None,
             last_action_message: None,
             current_execs: execs,
@@ -147,6 +141,124 @@ mod interactive_selection_tests {
         assert!(matches!(step, InteractiveSelectionStep::Break));
     }
 
+    #[test]
+    fn ctrl_r_reruns_and_selects_original_and_new_run() {
+        let i18n = I18n::new("en");
+        let execs = vec![exec_with("a", 1, "cat a"), exec_with("b", 2, "cat a")];
+        let mut state = InteractiveSelectionState {
+            filter_input: String::new(),
+            selected_ids: Vec::new(),
+            current_selection: 1,
+            preview_offset: 0,
+            show_help: false,
+            focus: SelectionFocus::Selection,
+            preview_mode: PreviewMode::Split,
+            pending_delete: None,
+            last_action_message: None,
+            current_execs: execs,
+            filtered_indices: vec![0, 1],
+        };
+        let mut loader = || Vec::<CommandExecution>::new();
+        let mut delete_action: Option<fn(&CommandExecution) -> Result<()>> = None;
+        let mut rerun_action = Some(|_: &CommandExecution| Ok(exec_with("c", 3, "cat a")));
+
+        let step = Differ::apply_interactive_selection_event(
+            &mut state,
+            Event::Key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL)),
+            &i18n,
+            2,
+            Some(24),
+            &mut loader,
+            &mut delete_action,
+            &mut rerun_action,
+        );
+        assert!(matches!(step, InteractiveSelectionStep::Break));
+        assert_eq!(state.selected_ids, vec!["b".to_string(), "c".to_string()]);
+        assert!(state
+            .current_execs
+            .iter()
+            .any(|e| e.record.record_id == "c"));
+    }
+
+    #[test]
+    fn preview_o_toggles_combined_mode() {
+        let i18n = I18n::new("en");
+        let mut state = InteractiveSelectionState {
+            filter_input: String::new(),
+            selected_ids: Vec::new(),
+            current_selection: 0,
+            preview_offset: 3,
+            show_help: false,
+            focus: SelectionFocus::Preview,
+            preview_mode: PreviewMode::Split,
+            pending_delete: None,
+            last_action_message: None,
+            current_execs: vec![exec_with("a", 1, "cat a")],
+            filtered_indices: vec![0],
+        };
+        let _ = apply_keys(
+            &mut state,
+            &i18n,
+            1,
+            &[(KeyCode::Char('o'), KeyModifiers::NONE)],
+        );
+        assert_eq!(state.preview_mode, PreviewMode::Combined);
+        assert_eq!(state.preview_offset, 0);
+        let _ = apply_keys(
+            &mut state,
+            &i18n,
+            1,
+            &[(KeyCode::Char('o'), KeyModifiers::NONE)],
+        );
+        assert_eq!(state.preview_mode, PreviewMode::Split);
+    }
+
+    #[test]
+    fn env_changes_list_added_removed_modified() {
+        let mut earlier = exec_with("a", 1, "env");
+        let mut later = exec_with("b", 2, "env");
+        assert!(Differ::env_change_lines(&earlier.record, &later.record).is_empty());
+
+        let env = |pairs: &[(&str, &str)]| {
+            Some(
+                pairs
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            )
+        };
+        earlier.record.env = env(&[("LANG", "C"), ("PATH", "/bin"), ("TZ", "UTC")]);
+        later.record.env = env(&[("LANG", "C"), ("PATH", "/usr/bin:/bin"), ("TERM", "xterm")]);
+        assert_eq!(
+            Differ::env_change_lines(&earlier.record, &later.record),
+            vec![
+                "~ PATH: /bin -> /usr/bin:/bin".to_string(),
+                "+ TERM=xterm".to_string(),
+                "- TZ=UTC".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn metric_change_reports_signed_delta() {
+        assert_eq!(Differ::metric_change(100, 130, "ms"), "+30ms, +30.0%");
+        assert_eq!(Differ::metric_change(200, 100, "KiB"), "-100KiB, -50.0%");
+        assert_eq!(Differ::metric_change(0, 5, ""), "+5, +0.0%");
+    }
+
+    #[test]
+    fn pty_output_compares_equal_to_piped_output() {
+        let piped = exec_with("a", 1, "ls");
+        let mut pty = exec_with("b", 2, "ls");
+        pty.record.pty = true;
+        pty.stdout = "\x1b[0m\x1b[01;34mout-a\x1b[0m\r\n".to_string();
+        assert_eq!(
+            Differ::comparable_output(&piped.record, &piped.stdout),
+            Differ::comparable_output(&pty.record, &pty.stdout)
+        );
+        assert_eq!(Differ::sanitize_for_preview("a\r\nb\r\n"), "a\nb\n");
+    }
+
     #[test]
     fn interactive_filter_and_backspace_updates_indices() {
         let i18n = I18n::new("en");
@@ -162,6 +274,7 @@ mod interactive_selection_tests {
             preview_offset: 0,
             show_help: false,
             focus: SelectionFocus::Selection,
+            preview_mode: PreviewMode::Split,
             pending_delete: None,
             last_action_message: None,
             current_execs: execs,
@@ -199,6 +312,14 @@ enum SelectionFocus {
     Preview,
 }
 
+/// What the preview pane shows for a run: stdout and stderr as separate sections,
+/// or both interleaved in the order they were produced.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PreviewMode {
+    Split,
+    Combined,
+}
+
 struct InteractiveSelectionState {
     filter_input: String,
     selected_ids: Vec<String>,
@@ -206,6 +327,7 @@ struct InteractiveSelectionState {
     preview_offset: u16,
     show_help: bool,
     focus: SelectionFocus,
+    preview_mode: PreviewMode,
     pending_delete: Option<CommandExecution>,
     last_action_message: Option<String>,
     current_execs: Vec<CommandExecution>,
@@ -271,16 +393,11 @@ impl Differ {
     /// - Convert carriage return `\r` to newline to preserve progress updates
     /// - Drop other C0 control chars (except `\n`), expand tabs to spaces
     fn sanitize_for_preview(text: &str) -> String {
-        // Compile ANSI regex once
-        static ANSI_RE: OnceLock<Regex> = OnceLock::new();
-        let re = ANSI_RE.get_or_init(|| {
-            // Covers ESC [ ... command (CSI sequences)
-            let pattern = r"\x1B\[[0-?]*[ -/]*[@-~]";
-            Regex::new(pattern).expect("valid ansi regex")
-        });
+        let re = Self::ansi_regex();
 
-        // Step 1: normalize carriage returns into newlines (typical for spinners/progress)
-        let mut s = text.replace('\r', "\n");
+        // Step 1: normalize carriage returns into newlines (typical for spinners/progress),
+        // keeping CRLF line endings (PTY captures) as a single line break
+        let mut s = text.replace("\r\n", "\n").replace('\r', "\n");
         // Step 2: strip ANSI sequences
         s = re.replace_all(&s, "").into_owned();
         // Step 3: remove other control chars and expand tabs
@@ -297,6 +414,106 @@ impl Differ {
         }
         cleaned
     }
+
+    fn ansi_regex() -> &'static Regex {
+        // Compile ANSI regex once
+        static ANSI_RE: OnceLock<Regex> = OnceLock::new();
+        ANSI_RE.get_or_init(|| {
+            // Covers ESC [ ... command (CSI sequences)
+            let pattern = r"\x1B\[[0-?]*[ -/]*[@-~]";
+            Regex::new(pattern).expect("valid ansi regex")
+        })
+    }
+
+    /// Output text as it should be compared. PTY captures carry CRLF line endings and
+    /// terminal escape sequences, which are normalized so they diff cleanly against
+    /// other runs.
+    fn comparable_output<'a>(record: &CommandRecord, text: &'a str) -> Cow<'a, str> {
+        if record.pty {
+            let s = text.replace("\r\n", "\n");
+            Cow::Owned(Self::ansi_regex().replace_all(&s, "").into_owned())
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    /// Environment differences between two runs: `+ NAME=value`, `- NAME=value`,
+    /// `~ NAME: old -> new`. Empty when either run has no captured environment.
+    fn env_change_lines(earlier: &CommandRecord, later: &CommandRecord) -> Vec<String> {
+        let (Some(old), Some(new)) = (&earlier.env, &later.env) else {
+            return Vec::new();
+        };
+        let mut lines = Vec::new();
+        for (key, value) in new {
+            match old.get(key) {
+                None => lines.push(format!("+ {}={}", key, value)),
+                Some(prev) if prev != value => {
+                    lines.push(format!("~ {}: {} -> {}", key, prev, value))
+                }
+                Some(_) => {}
+            }
+        }
+        for (key, value) in old {
+            if !new.contains_key(key) {
+                lines.push(format!("- {}={}", key, value));
+            }
+        }
+        lines
+    }
+
+    /// Resource metrics compared between two runs as `(i18n key, unit, before, after)`.
+    fn resource_metrics(
+        before: &ResourceUsage,
+        after: &ResourceUsage,
+    ) -> [(&'static str, &'static str, u64, u64); 3] {
+        [
+            ("diff_cpu_time", "ms", before.cpu_ms(), after.cpu_ms()),
+            ("diff_max_rss", "KiB", before.max_rss_kb, after.max_rss_kb),
+            (
+                "diff_ctx_switches",
+                "",
+                before.ctx_switches(),
+                after.ctx_switches(),
+            ),
+        ]
+    }
+
+    /// One translated line per resource metric, e.g. `cpu time: 120ms -> 150ms (+30ms, +25.0%)`.
+    fn resource_lines(before: &ResourceUsage, after: &ResourceUsage, i18n: &I18n) -> Vec<String> {
+        Self::resource_metrics(before, after)
+            .into_iter()
+            .map(|(key, unit, b, a)| {
+                i18n.t_format(
+                    key,
+                    &[
+                        &format!("{}{}", b, unit),
+                        &format!("{}{}", a, unit),
+                        &Self::metric_change(b, a, unit),
+                    ],
+                )
+            })
+            .collect()
+    }
+
+    /// Signed change between two measurements, e.g. `+30ms, +25.0%`.
+    fn metric_change(before: u64, after: u64, unit: &str) -> String {
+        let change = after as i64 - before as i64;
+        let pct = if before > 0 {
+            (change as f64 / before as f64) * 100.0
+        } else {
+            0.0
+        };
+        format!("{:+}{}, {:+.1}%", change, unit, pct)
+    }
+
+    fn capture_mode_label(record: &CommandRecord) -> &'static str {
+        if record.pty {
+            "pty"
+        } else {
+            "pipe"
+        }
+    }
+
     fn is_backspace_event(key: &KeyEvent) -> bool {
         matches!(key.code, KeyCode::Backspace)
             || matches!(key.code, KeyCode::Char(c) if c as u32 == 8 || c as u32 == 127)
@@ -601,6 +818,63 @@ impl Differ {
         false
     }
 
+    /// Re-run the highlighted record. With two selections to make, the original and
+    /// the new run become the selection so the caller diffs them right away.
+    fn handle_rerun_request<F, R>(
+        state: &mut InteractiveSelectionState,
+        rerun_action: &mut Option<R>,
+        loader: &mut F,
+        selection_goal: usize,
+        i18n: &I18n,
+    ) -> InteractiveSelectionStep
+    where
+        F: FnMut() -> Vec<CommandExecution>,
+        R: FnMut(&CommandExecution) -> Result<CommandExecution>,
+    {
+        let Some(rerun_fn) = rerun_action.as_mut() else {
+            return InteractiveSelectionStep::Continue {
+                needs_redraw: false,
+            };
+        };
+        Self::clear_delete_state(&mut state.pending_delete, &mut state.last_action_message);
+        let Some(&oi) = state.filtered_indices.get(state.current_selection) else {
+            return InteractiveSelectionStep::Continue {
+                needs_redraw: false,
+            };
+        };
+        let original = state.current_execs[oi].clone();
+        match rerun_fn(&original) {
+            Ok(new_exec) => {
+                state.filtered_indices =
+                    Self::reload_and_filter(&mut state.current_execs, loader, &state.filter_input);
+                if !state
+                    .current_execs
+                    .iter()
+                    .any(|e| e.record.record_id == new_exec.record.record_id)
+                {
+                    state.current_execs.push(new_exec.clone());
+                    state.filtered_indices =
+                        Self::compute_filtered_indices(&state.current_execs, &state.filter_input);
+                }
+                if selection_goal == 2 {
+                    state.selected_ids = vec![
+                        original.record.record_id.clone(),
+                        new_exec.record.record_id.clone(),
+                    ];
+                    return InteractiveSelectionStep::Break;
+                }
+                let code = new_exec.record.short_code.as_deref().unwrap_or("-");
+                state.last_action_message = Some(i18n.t_format("rerun_success_status", &[code]));
+            }
+            Err(err) => {
+                state.last_action_message =
+                    Some(i18n.t_format("rerun_failed_status", &[&err.to_string()]));
+            }
+        }
+        state.preview_offset = 0;
+        InteractiveSelectionStep::Continue { needs_redraw: true }
+    }
+
     pub fn select_prefix_for_clean(
         store: &StoreManager,
         i18n: &I18n,
@@ -1004,14 +1278,18 @@ impl Differ {
         let _ = terminal::disable_raw_mode();
         Ok(res)
     }
-    pub fn command_then_diff_flow(
+    pub fn command_then_diff_flow<R>(
         store: &StoreManager,
         i18n: &I18n,
         tui_simple: bool,
         use_alt_screen: bool,
         max_viewport: Option<usize>,
         linewise: bool,
-    ) -> Result<()> {
+        mut rerun_action: Option<R>,
+    ) -> Result<()>
+    where
+        R: FnMut(&CommandExecution) -> Result<CommandExecution>,
+    {
         // Build command groups from index
         let records = store.get_all_records()?;
         if records.is_empty() {
@@ -1059,6 +1337,7 @@ impl Differ {
                     true, // Esc returns empty => go back to command list
                     max_viewport,
                     Some(|exec: &CommandExecution| store_ref.delete_execution(exec, i18n)),
+                    rerun_action.as_mut(),
                     2,
                 );
                 if executions.is_empty() {
@@ -1081,11 +1360,26 @@ impl Differ {
         tui_simple: bool,
         use_alt_screen: bool,
     ) -> Result<()> {
+        if let Some(exec) = Self::command_then_select_flow(store, i18n, tui_simple, use_alt_screen)?
+        {
+            Self::show_execution(&exec, i18n)?;
+        }
+        Ok(())
+    }
+
+    /// Pick a command, then one of its executions. Cancelling the execution picker
+    /// returns to the command list; cancelling the command list returns `None`.
+    pub fn command_then_select_flow(
+        store: &StoreManager,
+        i18n: &I18n,
+        tui_simple: bool,
+        use_alt_screen: bool,
+    ) -> Result<Option<CommandExecution>> {
         // Build command groups from index
         let records = store.get_all_records()?;
         if records.is_empty() {
             println!("{}", i18n.t("no_records").yellow());
-            return Ok(());
+            return Ok(None);
         }
 
         loop {
@@ -1098,7 +1392,7 @@ impl Differ {
             };
 
             let Some(command_hash) = selected_hash else {
-                return Ok(());
+                return Ok(None);
             };
 
             // Load executions for the chosen command
@@ -1128,9 +1422,8 @@ impl Differ {
                 )
             };
 
-            if let Some(exec) = selected {
-                Self::show_execution(&exec, i18n)?;
-                return Ok(());
+            if selected.is_some() {
+                return Ok(selected);
             }
             // If selection was cancelled, go back to command list
         }
@@ -1155,7 +1448,7 @@ impl Differ {
             }
         }
         let mut groups: Vec<CommandGroup> = map.into_values().collect();
-        groups.sort_by(|a, b| b.latest.cmp(&a.latest));
+        groups.sort_by_key(|g| std::cmp::Reverse(g.latest));
         groups
     }
 
@@ -1439,9 +1732,35 @@ impl Differ {
                 later_code
             ));
         }
+        for (line, record) in [
+            (&mut earlier_line, &earlier.record),
+            (&mut later_line, &later.record),
+        ] {
+            if let Some(git) = &record.git {
+                line.push_str(&format!(" [git: {}]", git.label()));
+            }
+            if let Some(host) = &record.host {
+                line.push_str(&format!(" [{}: {}]", i18n.t("host_label"), host.label()));
+            }
+        }
         output.push_str(&format!("{}\n", earlier_line.red()));
         output.push_str(&format!("{}\n", later_line.green()));
 
+        if let (Some(before), Some(after)) = (&earlier.record.git, &later.record.git) {
+            if before.commit != after.commit {
+                output.push_str(&i18n.t_format(
+                    "diff_git_commit",
+                    &[before.short_commit(), after.short_commit()],
+                ));
+                output.push('\n');
+            }
+        }
+
+        if let Some((before, after)) = Self::host_change(&earlier.record, &later.record) {
+            output.push_str(&i18n.t_format("diff_host", &[&before, &after]));
+            output.push('\n');
+        }
+
         if earlier.record.exit_code != later.record.exit_code {
             output.push_str(&i18n.t_format(
                 "diff_exit_code",
@@ -1453,6 +1772,95 @@ impl Differ {
             output.push('\n');
         }
 
+        if earlier.record.pty != later.record.pty {
+            output.push_str(&i18n.t_format(
+                "diff_capture_mode",
+                &[
+                    Self::capture_mode_label(&earlier.record),
+                    Self::capture_mode_label(&later.record),
+                ],
+            ));
+            output.push('\n');
+        }
+
+        if earlier.record.shell_label() != later.record.shell_label() {
+            output.push_str(&i18n.t_format(
+                "diff_shell",
+                &[earlier.record.shell_label(), later.record.shell_label()],
+            ));
+            output.push('\n');
+        }
+
+        if earlier.record.termination != later.record.termination
+            || earlier.record.termination != TerminationReason::Completed
+        {
+            output.push_str(&i18n.t_format(
+                "diff_termination",
+                &[
+                    earlier.record.termination.as_str(),
+                    later.record.termination.as_str(),
+                ],
+            ));
+            output.push('\n');
+        }
+
+        if earlier.record.stdin_hash.is_some() || later.record.stdin_hash.is_some() {
+            if earlier.record.stdin_hash == later.record.stdin_hash {
+                output.push_str(&i18n.t_format(
+                    "diff_stdin_same",
+                    &[&later.record.stdin_label().unwrap_or_default()],
+                ));
+            } else {
+                let missing = i18n.t("stdin_not_recorded");
+                output.push_str(&i18n.t_format(
+                    "diff_stdin_changed",
+                    &[
+                        earlier.record.stdin_label().as_deref().unwrap_or(&missing),
+                        later.record.stdin_label().as_deref().unwrap_or(&missing),
+                    ],
+                ));
+            }
+            output.push('\n');
+        }
+
+        let env_lines = Self::env_change_lines(&earlier.record, &later.record);
+        if !env_lines.is_empty() {
+            output.push_str(&format!("{}\n", i18n.t("env_changed").yellow().bold()));
+            for line in &env_lines {
+                output.push_str(&format!("  {}\n", line));
+            }
+        }
+
+        let earlier_signal = earlier.record.signal_label();
+        let later_signal = later.record.signal_label();
+        if earlier_signal != later_signal {
+            let none = i18n.t("signal_none");
+            output.push_str(&i18n.t_format(
+                "diff_signal",
+                &[
+                    earlier_signal.as_deref().unwrap_or(&none),
+                    later_signal.as_deref().unwrap_or(&none),
+                ],
+            ));
+            output.push('\n');
+        }
+
+        for (label, execution) in [(&earlier_label, earlier), (&later_label, later)] {
+            for (stream, truncation) in Self::truncated_streams(&execution.record) {
+                output.push_str(&i18n.t_format(
+                    "diff_truncated",
+                    &[
+                        label.as_str(),
+                        stream,
+                        &truncation.head_bytes.to_string(),
+                        &truncation.tail_bytes.to_string(),
+                        &truncation.total_bytes.to_string(),
+                    ],
+                ));
+                output.push('\n');
+            }
+        }
+
         output.push_str(&i18n.t_format(
             "diff_execution_time",
             &[
@@ -1462,35 +1870,130 @@ impl Differ {
         ));
         output.push('\n');
 
+        if let (Some(before), Some(after)) = (&earlier.record.resources, &later.record.resources) {
+            for line in Self::resource_lines(before, after, i18n) {
+                output.push_str(&line);
+                output.push('\n');
+            }
+        }
+
         output.push('\n');
 
-        if earlier.stdout != later.stdout {
+        let earlier_stdout = Self::comparable_output(&earlier.record, &earlier.stdout);
+        let later_stdout = Self::comparable_output(&later.record, &later.stdout);
+        let earlier_stderr = Self::comparable_output(&earlier.record, &earlier.stderr);
+        let later_stderr = Self::comparable_output(&later.record, &later.stderr);
+
+        if earlier_stdout != later_stdout {
             output.push_str(&format!("{}\n", i18n.t("stdout_diff").yellow().bold()));
             if linewise {
-                output.push_str(&Self::diff_text_linewise(&earlier.stdout, &later.stdout));
+                output.push_str(&Self::diff_text_linewise(&earlier_stdout, &later_stdout));
             } else {
-                output.push_str(&Self::diff_text(&earlier.stdout, &later.stdout));
+                output.push_str(&Self::diff_text(&earlier_stdout, &later_stdout));
             }
             output.push('\n');
         }
 
-        if earlier.stderr != later.stderr {
+        if earlier_stderr != later_stderr {
             output.push_str(&format!("{}\n", i18n.t("stderr_diff").red().bold()));
             if linewise {
-                output.push_str(&Self::diff_text_linewise(&earlier.stderr, &later.stderr));
+                output.push_str(&Self::diff_text_linewise(&earlier_stderr, &later_stderr));
             } else {
-                output.push_str(&Self::diff_text(&earlier.stderr, &later.stderr));
+                output.push_str(&Self::diff_text(&earlier_stderr, &later_stderr));
+            }
+            output.push('\n');
+        }
+
+        let file_changes = artifacts::changed_files(&earlier.record, &later.record);
+        if let Some((changes, unchanged)) = &file_changes {
+            if !changes.is_empty() {
+                output.push_str(&format!("{}\n", i18n.t("tracked_files_diff").cyan().bold()));
+                output.push_str(&Self::tracked_files_text(
+                    earlier,
+                    later,
+                    changes,
+                    i18n,
+                    |old, new| {
+                        if linewise {
+                            Self::diff_text_linewise(old, new)
+                        } else {
+                            Self::diff_text(old, new)
+                        }
+                    },
+                ));
+            }
+            if *unchanged > 0 {
+                output.push_str(&format!(
+                    "{}\n",
+                    i18n.t_format("tracked_files_unchanged", &[&unchanged.to_string()])
+                        .dimmed()
+                ));
             }
             output.push('\n');
         }
 
-        if earlier.stdout == later.stdout && earlier.stderr == later.stderr {
+        let files_identical = file_changes.is_none_or(|(changes, _)| changes.is_empty());
+        if earlier_stdout == later_stdout && earlier_stderr == later_stderr && files_identical {
             output.push_str(&format!("{}\n", i18n.t("output_identical").green().bold()));
         }
 
         Some(output)
     }
 
+    /// Per-file report of tracked files that changed between two runs: text
+    /// files are diffed with `diff`, binary ones summarised by size.
+    fn tracked_files_text(
+        earlier: &CommandExecution,
+        later: &CommandExecution,
+        changes: &[FileChange],
+        i18n: &I18n,
+        diff: impl Fn(&str, &str) -> String,
+    ) -> String {
+        let size = |execution: &CommandExecution, index: usize| {
+            execution
+                .record
+                .artifacts
+                .as_ref()
+                .map_or(0, |tracked| tracked.files[index].size)
+                .to_string()
+        };
+        let mut out = String::new();
+        for change in changes {
+            let path = change.path.display().to_string();
+            match (change.before, change.after) {
+                (Some(before), Some(after)) => {
+                    out.push_str(&i18n.t_format("tracked_file_modified", &[&path]));
+                    out.push('\n');
+                    match (
+                        artifacts::file_text(earlier, before),
+                        artifacts::file_text(later, after),
+                    ) {
+                        (Some(old), Some(new)) => out.push_str(&diff(&old, &new)),
+                        _ => {
+                            out.push_str(&i18n.t_format(
+                                "tracked_file_binary",
+                                &[&size(earlier, before), &size(later, after)],
+                            ));
+                            out.push('\n');
+                        }
+                    }
+                }
+                (None, Some(after)) => {
+                    out.push_str(
+                        &i18n.t_format("tracked_file_added", &[&path, &size(later, after)]),
+                    );
+                    out.push('\n');
+                }
+                (Some(_), None) => {
+                    out.push_str(&i18n.t_format("tracked_file_removed", &[&path]));
+                    out.push('\n');
+                }
+                (None, None) => {}
+            }
+        }
+        out
+    }
+
     // compute_preview_layout removed (ratatui handles layout)
 
     fn char_display_width(ch: char) -> usize {
@@ -1679,7 +2182,7 @@ impl Differ {
     }
 
     #[allow(clippy::too_many_arguments)]
-    fn apply_interactive_selection_event<F, D>(
+    fn apply_interactive_selection_event<F, D, R>(
         state: &mut InteractiveSelectionState,
         event: Event,
         i18n: &I18n,
@@ -1687,10 +2190,12 @@ impl Differ {
         terminal_height: Option<u16>,
         loader: &mut F,
         delete_action: &mut Option<D>,
+        rerun_action: &mut Option<R>,
     ) -> InteractiveSelectionStep
     where
         F: FnMut() -> Vec<CommandExecution>,
         D: FnMut(&CommandExecution) -> Result<()>,
+        R: FnMut(&CommandExecution) -> Result<CommandExecution>,
     {
         match event {
             Event::Key(key_event) => {
@@ -1738,6 +2243,15 @@ impl Differ {
                         );
                         return InteractiveSelectionStep::Continue { needs_redraw };
                     }
+                    KeyCode::Char('r') if ctrl => {
+                        return Self::handle_rerun_request(
+                            state,
+                            rerun_action,
+                            loader,
+                            selection_goal,
+                            i18n,
+                        );
+                    }
                     KeyCode::Backspace if shift => {
                         let needs_redraw = Self::handle_shift_backspace(
                             delete_action,
@@ -2137,6 +2651,21 @@ impl Differ {
                             state.show_help = !state.show_help;
                             InteractiveSelectionStep::Continue { needs_redraw: true }
                         }
+                        KeyCode::Char('o') if !ctrl && !alt => {
+                            state.preview_mode = match state.preview_mode {
+                                PreviewMode::Split => PreviewMode::Combined,
+                                PreviewMode::Combined => PreviewMode::Split,
+                            };
+                            state.preview_offset = 0;
+                            InteractiveSelectionStep::Continue { needs_redraw: true }
+                        }
+                        KeyCode::Char('r') if !ctrl && !alt => Self::handle_rerun_request(
+                            state,
+                            rerun_action,
+                            loader,
+                            selection_goal,
+                            i18n,
+                        ),
                         KeyCode::Char('q') => {
                             state.focus = SelectionFocus::Selection;
                             InteractiveSelectionStep::Continue { needs_redraw: true }
@@ -2189,7 +2718,7 @@ impl Differ {
     }
 
     #[allow(clippy::too_many_arguments)]
-    fn start_interactive_selection_ratatui<F, D>(
+    fn start_interactive_selection_ratatui<F, D, R>(
         executions: &[CommandExecution],
         i18n: &I18n,
         use_alt_screen: bool,
@@ -2198,11 +2727,13 @@ impl Differ {
         _on_escape_return_empty: bool,
         _max_viewport: Option<usize>,
         mut delete_action: Option<D>,
+        mut rerun_action: Option<R>,
         selection_goal: usize,
     ) -> Vec<CommandExecution>
     where
         F: FnMut() -> Vec<CommandExecution>,
         D: FnMut(&CommandExecution) -> Result<()>,
+        R: FnMut(&CommandExecution) -> Result<CommandExecution>,
     {
         if terminal::enable_raw_mode().is_err() {
             println!("{}", i18n.t("warning_interactive_failed"));
@@ -2233,12 +2764,14 @@ impl Differ {
             preview_offset: 0,
             show_help: false,
             focus: SelectionFocus::Selection,
+            preview_mode: PreviewMode::Split,
             pending_delete: None,
             last_action_message: None,
             current_execs,
             filtered_indices,
         };
         let mut needs_redraw = true;
+        let can_rerun = rerun_action.is_some();
 
         loop {
             if needs_redraw {
@@ -2255,7 +2788,9 @@ impl Differ {
                         linewise,
                         selection_goal,
                         matches!(state.focus, SelectionFocus::Preview),
+                        state.preview_mode,
                         state.show_help,
+                        can_rerun,
                         state.last_action_message.as_deref(),
                     )
                 });
@@ -2276,6 +2811,7 @@ impl Differ {
                 terminal_height,
                 &mut loader,
                 &mut delete_action,
+                &mut rerun_action,
             ) {
                 InteractiveSelectionStep::Continue { needs_redraw: nr } => {
                     needs_redraw |= nr;
@@ -2310,7 +2846,7 @@ impl Differ {
                 })
                 .collect();
             if picked.len() == selection_goal {
-                picked.sort_by(|a, b| a.record.timestamp.cmp(&b.record.timestamp));
+                picked.sort_by_key(|e| e.record.timestamp);
                 return picked;
             }
         }
@@ -2330,7 +2866,9 @@ impl Differ {
         linewise: bool,
         selection_goal: usize,
         preview_focused: bool,
+        preview_mode: PreviewMode,
         show_help: bool,
+        can_rerun: bool,
         last_action: Option<&str>,
     ) {
         // Ensure the frame is fully cleared to avoid artifacts under the UI
@@ -2369,7 +2907,7 @@ impl Differ {
             } else {
                 "  "
             };
-            let line = if let Some(code) = exec.record.short_code.as_deref() {
+            let mut line = if let Some(code) = exec.record.short_code.as_deref() {
                 format!(
                     "{}{}: {}:{} {}: {}",
                     mark,
@@ -2388,6 +2926,16 @@ impl Differ {
                     date_str
                 )
             };
+            if let Some(git) = &exec.record.git {
+                line.push_str(&format!(
+                    " @{}{}",
+                    git.short_commit(),
+                    if git.dirty { "*" } else { "" }
+                ));
+            }
+            if exec.record.termination != TerminationReason::Completed {
+                line.push_str(&format!(" [{}]", exec.record.termination));
+            }
             items.push(ListItem::new(line));
         }
         let list_title = i18n.t_format("select_executions", &[&current_execs.len().to_string()]);
@@ -2415,7 +2963,7 @@ impl Differ {
                 .filter_map(|id| current_execs.iter().find(|e| &e.record.record_id == id))
                 .collect();
             if pair.len() == 2 {
-                pair.sort_by(|a, b| a.record.timestamp.cmp(&b.record.timestamp));
+                pair.sort_by_key(|e| e.record.timestamp);
                 Some((pair[0], pair[1]))
             } else {
                 None
@@ -2430,7 +2978,33 @@ impl Differ {
 
         // Build combined preview: stdout then stderr (with divider if both exist)
         let mut title = i18n.t("preview_stdout_header");
-        let body = if let Some((earlier, later)) = preview_pair {
+        let combined_pair = preview_pair
+            .filter(|_| preview_mode == PreviewMode::Combined)
+            .and_then(|(earlier, later)| {
+                Some((earlier.combined_output()?, later.combined_output()?))
+            });
+        let combined_focus = focus_exec
+            .filter(|_| preview_mode == PreviewMode::Combined)
+            .map(|exec| exec.combined_output());
+        let body = if let Some((old, new)) = combined_pair {
+            title = i18n.t("preview_combined_header");
+            let old = Self::sanitize_for_preview(&old);
+            let new = Self::sanitize_for_preview(&new);
+            if old == new {
+                i18n.t("output_identical")
+            } else if linewise {
+                Self::diff_preview_text_linewise(&old, &new)
+            } else {
+                Self::diff_preview_text(&old, &new)
+            }
+        } else if let (None, Some(combined)) = (preview_pair, combined_focus) {
+            title = i18n.t("preview_combined_header");
+            match combined {
+                Some(text) if text.is_empty() => i18n.t("preview_empty"),
+                Some(text) => Self::sanitize_for_preview(&text),
+                None => i18n.t("preview_no_timeline"),
+            }
+        } else if let Some((earlier, later)) = preview_pair {
             let so_old = Self::sanitize_for_preview(&earlier.stdout);
             let so_new = Self::sanitize_for_preview(&later.stdout);
             let se_old = Self::sanitize_for_preview(&earlier.stderr);
@@ -2463,6 +3037,46 @@ impl Differ {
             } else {
                 title = i18n.t("preview_diff_stdout_header");
             }
+            let env_lines = Self::env_change_lines(&earlier.record, &later.record);
+            if !env_lines.is_empty() {
+                out.push_str(&format!("\n── {} ──\n", i18n.t("env_changed")));
+                out.push_str(&env_lines.join("\n"));
+                out.push('\n');
+            }
+            if earlier.record.git != later.record.git {
+                let none = i18n.t("git_none");
+                let label =
+                    |git: &Option<GitInfo>| git.as_ref().map_or(none.clone(), |g| g.label());
+                out.push_str(&format!("\n── {} ──\n", i18n.t("git_changed")));
+                out.push_str(&format!("- {}\n", label(&earlier.record.git)));
+                out.push_str(&format!("+ {}\n", label(&later.record.git)));
+            }
+            if let Some((before, after)) = Self::host_change(&earlier.record, &later.record) {
+                out.push_str(&format!("\n── {} ──\n", i18n.t("host_changed")));
+                out.push_str(&format!("- {}\n+ {}\n", before, after));
+            }
+            if let Some((changes, _)) = artifacts::changed_files(&earlier.record, &later.record) {
+                if !changes.is_empty() {
+                    out.push_str(&format!("\n── {} ──\n", i18n.t("tracked_files_diff")));
+                    out.push_str(&Self::tracked_files_text(
+                        earlier,
+                        later,
+                        &changes,
+                        i18n,
+                        |old, new| {
+                            let (old, new) = (
+                                Self::sanitize_for_preview(old),
+                                Self::sanitize_for_preview(new),
+                            );
+                            if linewise {
+                                Self::diff_preview_text_linewise(&old, &new)
+                            } else {
+                                Self::diff_preview_text(&old, &new)
+                            }
+                        },
+                    ));
+                }
+            }
             out
         } else if let Some(exec) = focus_exec {
             let so = Self::sanitize_for_preview(&exec.stdout);
@@ -2487,6 +3101,12 @@ impl Differ {
             }
             let empty_label = i18n.t("preview_empty");
             let mut lines: Vec<String> = Vec::new();
+            if let Some(git) = &exec.record.git {
+                lines.push(i18n.t_format("preview_git_label", &[&git.label()]));
+            }
+            if let Some(host) = &exec.record.host {
+                lines.push(i18n.t_format("preview_host_label", &[&host.describe()]));
+            }
             let stdout_heading = i18n.t("stdout");
             lines.push(format!("{} {}", stdout_heading, stdout_path_text));
             if so.is_empty() {
@@ -2500,6 +3120,13 @@ impl Differ {
                 lines.push(format!("{} {}", stderr_heading, stderr_path_text));
                 lines.push(se);
             }
+            if let Some(tracked) = &exec.record.artifacts {
+                lines.push(String::new());
+                lines.push(i18n.t("preview_tracked_files"));
+                for file in &tracked.files {
+                    lines.push(format!("  {} ({})", file.path.display(), file.size));
+                }
+            }
             lines.join("\n")
         } else {
             i18n.t("preview_no_selection")
@@ -2568,7 +3195,7 @@ impl Differ {
             let popup = {
                 let area = list_area;
                 let w = (area.width as f32 * 0.8) as u16;
-                let h = 13u16;
+                let h = 13u16 + u16::from(can_rerun);
                 let x = area.x + (area.width.saturating_sub(w)) / 2;
                 let y = area.y + (area.height.saturating_sub(h)) / 2;
                 ratatui::layout::Rect {
@@ -2578,7 +3205,7 @@ impl Differ {
                     height: h,
                 }
             };
-            let lines = [
+            let mut lines = vec![
                 String::new(),
                 i18n.t("selection_help_filter"),
                 i18n.t("selection_help_move"),
@@ -2588,12 +3215,15 @@ impl Differ {
                 i18n.t("selection_help_preview"),
                 i18n.t("selection_help_clear"),
                 i18n.t("selection_help_delete"),
-                format!(
-                    "{}   {}",
-                    i18n.t("preview_help_toggle"),
-                    i18n.t("preview_help_quit")
-                ),
             ];
+            if can_rerun {
+                lines.push(i18n.t("selection_help_rerun"));
+            }
+            lines.push(format!(
+                "{}   {}",
+                i18n.t("preview_help_toggle"),
+                i18n.t("preview_help_quit")
+            ));
             let help_text = Paragraph::new(lines.join("\n"))
                 .block(
                     Block::default()
@@ -2611,7 +3241,7 @@ impl Differ {
             let popup = {
                 let area = preview_area;
                 let w = (area.width as f32 * 0.7) as u16;
-                let h = 12u16;
+                let h = 12u16 + u16::from(can_rerun);
                 let x = area.x + (area.width.saturating_sub(w)) / 2;
                 let y = area.y + (area.height.saturating_sub(h)) / 2;
                 ratatui::layout::Rect {
@@ -2621,7 +3251,7 @@ impl Differ {
                     height: h,
                 }
             };
-            let lines = [
+            let mut lines = vec![
                 String::new(),
                 i18n.t("preview_help_move"),
                 i18n.t("preview_help_page"),
@@ -2629,12 +3259,16 @@ impl Differ {
                 i18n.t("preview_help_top_bottom"),
                 i18n.t("preview_help_back"),
                 i18n.t("preview_help_start_diff"),
-                format!(
-                    "{}   {}",
-                    i18n.t("preview_help_toggle"),
-                    i18n.t("preview_help_quit")
-                ),
+                i18n.t("preview_help_combined"),
             ];
+            if can_rerun {
+                lines.push(i18n.t("preview_help_rerun"));
+            }
+            lines.push(format!(
+                "{}   {}",
+                i18n.t("preview_help_toggle"),
+                i18n.t("preview_help_quit")
+            ));
             let help_text = Paragraph::new(lines.join("\n"))
                 .block(
                     Block::default()
@@ -2691,7 +3325,7 @@ impl Differ {
     }
 
     #[allow(clippy::too_many_arguments)]
-    pub fn interactive_select_executions_with_loader<F, D>(
+    pub fn interactive_select_executions_with_loader<F, D, R>(
         executions: &[CommandExecution],
         i18n: &I18n,
         tui_simple: bool,
@@ -2700,10 +3334,12 @@ impl Differ {
         linewise: bool,
         loader: F,
         delete_action: Option<D>,
+        rerun_action: Option<R>,
     ) -> Vec<CommandExecution>
     where
         F: FnMut() -> Vec<CommandExecution>,
         D: FnMut(&CommandExecution) -> Result<()>,
+        R: FnMut(&CommandExecution) -> Result<CommandExecution>,
     {
         if tui_simple {
             return Self::simple_select_executions(executions, i18n);
@@ -2717,6 +3353,7 @@ impl Differ {
             false,
             max_viewport,
             delete_action,
+            rerun_action,
             2,
         )
     }
@@ -2748,6 +3385,7 @@ impl Differ {
             false,
             max_viewport,
             delete_action,
+            None::<fn(&CommandExecution) -> Result<CommandExecution>>,
             1,
         );
         picked.into_iter().next()
@@ -2755,20 +3393,9 @@ impl Differ {
 
     /// Show stdout/stderr of a single execution
     pub fn show_execution(execution: &CommandExecution, i18n: &I18n) -> Result<()> {
-        use std::fs;
-
-        let stdout = if let Some(path) = execution.stdout_path.as_ref() {
-            let bytes = fs::read(path)?;
-            String::from_utf8_lossy(&bytes).to_string()
-        } else {
-            execution.stdout.clone()
-        };
-        let stderr = if let Some(path) = execution.stderr_path.as_ref() {
-            let bytes = fs::read(path)?;
-            String::from_utf8_lossy(&bytes).to_string()
-        } else {
-            execution.stderr.clone()
-        };
+        // Output was decoded with the recorded (or overridden) encoding when loaded.
+        let stdout = &execution.stdout;
+        let stderr = &execution.stderr;
 
         println!("{}", i18n.t("show_stdout_header"));
         print!("{}", stdout);
@@ -2854,7 +3481,7 @@ impl Differ {
             selected.push(executions[i - 1].clone());
         }
 
-        selected.sort_by(|a, b| a.record.timestamp.cmp(&b.record.timestamp));
+        selected.sort_by_key(|e| e.record.timestamp);
         selected
     }
 
@@ -2979,7 +3606,7 @@ impl Differ {
         // If only one picked or duplicates, add another record (latest not the same)
         if selected.len() < 2 {
             let mut sorted = executions.to_vec();
-            sorted.sort_by(|a, b| b.record.timestamp.cmp(&a.record.timestamp));
+            sorted.sort_by_key(|e| std::cmp::Reverse(e.record.timestamp));
             for e in sorted {
                 if selected
                     .iter()
@@ -2996,7 +3623,7 @@ impl Differ {
             return executions.iter().take(2).cloned().collect();
         }
 
-        selected.sort_by(|a, b| a.record.timestamp.cmp(&b.record.timestamp));
+        selected.sort_by_key(|e| e.record.timestamp);
         selected
     }
 
@@ -3049,9 +3676,9 @@ impl Differ {
         }
 
         // If more than two matches, choose the latest two
-        filtered.sort_by(|a, b| b.record.timestamp.cmp(&a.record.timestamp));
+        filtered.sort_by_key(|e| std::cmp::Reverse(e.record.timestamp));
         filtered.truncate(2);
-        filtered.sort_by(|a, b| a.record.timestamp.cmp(&b.record.timestamp));
+        filtered.sort_by_key(|e| e.record.timestamp);
 
         println!("{}", i18n.t("using_filtered_records"));
         for exec in &filtered {
@@ -3139,12 +3766,52 @@ impl Differ {
         }
     }
 
+    /// Whether two runs produced different stdout, stderr or tracked files
+    /// (ignoring PTY line endings and colour codes, like the diff itself).
+    pub fn outputs_differ(earlier: &CommandExecution, later: &CommandExecution) -> bool {
+        // Equal blob hashes mean byte-identical output; only differing (or
+        // unknown) hashes need the normalized comparison. Outputs only partly
+        // held in memory cannot be normalized, so differing hashes decide.
+        let same_blob = |a: &Option<String>, b: &Option<String>| a.is_some() && a == b;
+        let (before, after) = (&earlier.record, &later.record);
+        artifacts::changed_files(before, after).is_some_and(|(changes, _)| !changes.is_empty())
+            || (!same_blob(&before.stdout_sha256, &after.stdout_sha256)
+                && (earlier.stdout_view.is_some()
+                    || later.stdout_view.is_some()
+                    || Self::comparable_output(before, &earlier.stdout)
+                        != Self::comparable_output(after, &later.stdout)))
+            || (!same_blob(&before.stderr_sha256, &after.stderr_sha256)
+                && (earlier.stderr_view.is_some()
+                    || later.stderr_view.is_some()
+                    || Self::comparable_output(before, &earlier.stderr)
+                        != Self::comparable_output(after, &later.stderr)))
+    }
+
+    /// Descriptions of the hosts two runs were recorded on, when they differ.
+    /// Runs from before hosts were recorded are not reported as a change.
+    fn host_change(earlier: &CommandRecord, later: &CommandRecord) -> Option<(String, String)> {
+        let (before, after) = (earlier.host.as_ref()?, later.host.as_ref()?);
+        (before != after).then(|| (before.describe(), after.describe()))
+    }
+
+    /// Streams of a record that exceeded `storage.max_output_bytes`.
+    fn truncated_streams(record: &CommandRecord) -> Vec<(&'static str, OutputTruncation)> {
+        [
+            ("stdout", record.stdout_truncation),
+            ("stderr", record.stderr_truncation),
+        ]
+        .into_iter()
+        .filter_map(|(stream, truncation)| Some((stream, truncation?)))
+        .collect()
+    }
+
     /// Automatically diff current execution against a target (first/last) execution.
     /// Outputs colored diff directly to stdout without TUI interaction.
     pub fn auto_diff(
         current: &CommandExecution,
         target: &CommandExecution,
         target_type: crate::DiffTarget,
+        i18n: &I18n,
     ) -> Result<()> {
         let target_time = target.record.timestamp.with_timezone(&Local);
         println!();
@@ -3176,6 +3843,96 @@ impl Differ {
             );
         }
 
+        if target.record.pty != current.record.pty {
+            let line = i18n.t_format(
+                "diff_capture_mode",
+                &[
+                    Self::capture_mode_label(&target.record),
+                    Self::capture_mode_label(&current.record),
+                ],
+            );
+            println!("{}", line.yellow());
+        }
+
+        if target.record.stdin_hash != current.record.stdin_hash {
+            let missing = i18n.t("stdin_not_recorded");
+            let line = i18n.t_format(
+                "diff_stdin_changed",
+                &[
+                    target.record.stdin_label().as_deref().unwrap_or(&missing),
+                    current.record.stdin_label().as_deref().unwrap_or(&missing),
+                ],
+            );
+            println!("{}", line.yellow());
+        }
+
+        let env_lines = Self::env_change_lines(&target.record, &current.record);
+        if !env_lines.is_empty() {
+            println!("{}", i18n.t("env_changed").yellow());
+            for line in &env_lines {
+                println!("  {}", line);
+            }
+        }
+
+        let target_signal = target.record.signal_label();
+        let current_signal = current.record.signal_label();
+        if target_signal != current_signal {
+            let none = i18n.t("signal_none");
+            let line = i18n.t_format(
+                "diff_signal",
+                &[
+                    target_signal.as_deref().unwrap_or(&none),
+                    current_signal.as_deref().unwrap_or(&none),
+                ],
+            );
+            println!("{}", line.yellow());
+        }
+
+        if let Some((before, after)) = Self::host_change(&target.record, &current.record) {
+            println!(
+                "{}",
+                i18n.t_format("diff_host", &[&before, &after]).yellow()
+            );
+        }
+
+        if target.record.shell_label() != current.record.shell_label() {
+            let line = i18n.t_format(
+                "diff_shell",
+                &[target.record.shell_label(), current.record.shell_label()],
+            );
+            println!("{}", line.yellow());
+        }
+
+        if target.record.termination != current.record.termination {
+            let line = i18n.t_format(
+                "diff_termination",
+                &[
+                    target.record.termination.as_str(),
+                    current.record.termination.as_str(),
+                ],
+            );
+            println!("{}", line.yellow());
+        }
+
+        for (label, execution) in [
+            (i18n.t("diff_earlier_label"), target),
+            (i18n.t("diff_later_label"), current),
+        ] {
+            for (stream, truncation) in Self::truncated_streams(&execution.record) {
+                let line = i18n.t_format(
+                    "diff_truncated",
+                    &[
+                        label.as_str(),
+                        stream,
+                        &truncation.head_bytes.to_string(),
+                        &truncation.tail_bytes.to_string(),
+                        &truncation.total_bytes.to_string(),
+                    ],
+                );
+                println!("{}", line.yellow());
+            }
+        }
+
         // Show duration comparison
         let duration_change = current.record.duration_ms as i64 - target.record.duration_ms as i64;
         let duration_pct = if target.record.duration_ms > 0 {
@@ -3193,26 +3950,51 @@ impl Differ {
             duration_pct
         );
         println!("{}", duration_str);
+        if let (Some(before), Some(after)) = (&target.record.resources, &current.record.resources) {
+            for line in Self::resource_lines(before, after, i18n) {
+                println!("{}", line.dimmed());
+            }
+        }
         println!();
 
         // Diff stdout
-        if target.stdout == current.stdout {
+        let target_stdout = Self::comparable_output(&target.record, &target.stdout);
+        let current_stdout = Self::comparable_output(&current.record, &current.stdout);
+        if target_stdout == current_stdout {
             println!("{}", "✓ stdout identical".green());
         } else {
             println!("{}", "stdout diff:".yellow().bold());
-            print!("{}", Self::diff_text(&target.stdout, &current.stdout));
+            print!("{}", Self::diff_text(&target_stdout, &current_stdout));
         }
         println!();
 
         // Diff stderr
-        if target.stderr == current.stderr {
+        let target_stderr = Self::comparable_output(&target.record, &target.stderr);
+        let current_stderr = Self::comparable_output(&current.record, &current.stderr);
+        if target_stderr == current_stderr {
             println!("{}", "✓ stderr identical".green());
         } else {
             println!("{}", "stderr diff:".red().bold());
-            print!("{}", Self::diff_text(&target.stderr, &current.stderr));
+            print!("{}", Self::diff_text(&target_stderr, &current_stderr));
         }
         println!();
 
+        if let Some((changes, unchanged)) =
+            artifacts::changed_files(&target.record, &current.record)
+        {
+            if changes.is_empty() {
+                let line = i18n.t_format("tracked_files_unchanged", &[&unchanged.to_string()]);
+                println!("{}", format!("✓ {}", line).green());
+            } else {
+                println!("{}", i18n.t("tracked_files_diff").cyan().bold());
+                print!(
+                    "{}",
+                    Self::tracked_files_text(target, current, &changes, i18n, Self::diff_text)
+                );
+            }
+            println!();
+        }
+
         Ok(())
     }
 }
@@ -3285,7 +4067,7 @@ mod test_support {
                 selected.push(executions[i - 1].clone());
             }
 
-            selected.sort_by(|a, b| a.record.timestamp.cmp(&b.record.timestamp));
+            selected.sort_by_key(|e| e.record.timestamp);
             selected
         }
 
@@ -3313,6 +4095,7 @@ mod test_support {
                 false,
                 None,
                 None::<fn(&CommandExecution) -> Result<()>>,
+                None::<fn(&CommandExecution) -> Result<CommandExecution>>,
                 2,
             )
         }
@@ -0,0 +1,108 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+/// Resolve a user supplied encoding label (e.g. `latin1`, `utf-16le`, `gbk`).
+pub fn lookup(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+/// clap value parser for `--encoding`.
+pub fn parse_label(label: &str) -> Result<&'static Encoding, String> {
+    lookup(label).ok_or_else(|| format!("Unknown encoding '{}'", label))
+}
+
+/// Guess the text encoding of captured output.
+/// BOMs win, then NUL-interleaved UTF-16, then valid UTF-8; otherwise fall back to
+/// statistical detection.
+pub fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    // Checked before UTF-8: ASCII-heavy UTF-16 is also technically valid UTF-8.
+    if let Some(encoding) = detect_utf16_without_bom(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, true)
+}
+
+/// UTF-16 text that is mostly ASCII has a NUL in every other byte.
+fn detect_utf16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    if bytes.len() < 4 || bytes.len() % 2 == 1 {
+        return None;
+    }
+    let pairs = bytes.len() / 2;
+    let even_nuls = bytes.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_nuls = bytes.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+    if odd_nuls * 10 >= pairs * 7 && even_nuls * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even_nuls * 10 >= pairs * 7 && odd_nuls * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Decode bytes with the given encoding. Malformed sequences become U+FFFD,
+/// but the raw bytes on disk stay untouched.
+pub fn decode(bytes: &[u8], encoding: &'static Encoding) -> String {
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+/// Decode stored output: an explicit override wins, then the encoding recorded
+/// at capture time, then detection (records written before encodings were tracked).
+pub fn decode_stored(
+    bytes: &[u8],
+    recorded: Option<&str>,
+    override_encoding: Option<&'static Encoding>,
+) -> String {
+    let encoding = override_encoding
+        .or_else(|| recorded.and_then(lookup))
+        .unwrap_or_else(|| detect(bytes));
+    decode(bytes, encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8_is_kept() {
+        assert_eq!(detect("héllo 世界".as_bytes()), UTF_8);
+        assert_eq!(detect(b""), UTF_8);
+    }
+
+    #[test]
+    fn latin1_is_not_mangled() {
+        let bytes = b"caf\xe9 cr\xe8me br\xfbl\xe9e, na\xefve fa\xe7ade\n";
+        let encoding = detect(bytes);
+        assert_ne!(encoding, UTF_8);
+        assert_eq!(decode(bytes, encoding), "café crème brûlée, naïve façade\n");
+    }
+
+    #[test]
+    fn utf16_with_and_without_bom() {
+        let le: Vec<u8> = "hello\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        assert_eq!(detect(&le), UTF_16LE);
+        assert_eq!(decode(&le, UTF_16LE), "hello\n");
+
+        let mut be = vec![0xFE, 0xFF];
+        be.extend("hi".encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(detect(&be), UTF_16BE);
+        assert_eq!(decode(&be, detect(&be)), "hi");
+    }
+
+    #[test]
+    fn override_wins_over_recorded() {
+        let bytes = b"\xe9";
+        assert_eq!(decode_stored(bytes, Some("UTF-8"), None), "\u{FFFD}");
+        assert_eq!(decode_stored(bytes, Some("UTF-8"), lookup("latin1")), "é");
+    }
+}
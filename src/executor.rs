@@ -1,25 +1,859 @@
-use crate::storage::{self, CommandExecution, CommandRecord};
+use crate::storage::{
+    self, CommandExecution, CommandRecord, OutputStream, OutputTruncation, ResourceUsage,
+    TerminationReason, TimelineChunk,
+};
 use anyhow::{anyhow, Context, Result};
 use chrono::Utc;
-use std::io::{self, Read, Write};
-use std::process::{Command, Stdio};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Options controlling how a command is spawned and captured.
+#[derive(Debug, Clone, Default)]
+pub struct ExecOptions {
+    /// Run the command under a pseudo-terminal so it behaves as if attached to a TTY.
+    pub pty: bool,
+    /// Stop the command once this much time has elapsed.
+    pub timeout: Option<Duration>,
+    /// How long to wait after SIGTERM before escalating to SIGKILL.
+    pub kill_grace: Duration,
+    /// Decode output with this encoding instead of detecting it.
+    pub encoding: Option<&'static encoding_rs::Encoding>,
+    /// Where the command's stdin comes from.
+    pub stdin: StdinSource,
+    /// Environment variables to record (glob patterns, `*` wildcard).
+    pub env_allowlist: Vec<String>,
+    /// Environment variables never recorded, even when allowlisted.
+    pub env_denylist: Vec<String>,
+    /// Shell program and leading arguments (e.g. `bash -o pipefail`); empty means `sh`.
+    pub shell: Vec<String>,
+    /// Capture output without echoing it to the terminal.
+    pub quiet: bool,
+    /// Directory output is streamed into while the command runs; `None` keeps it in memory.
+    pub capture_dir: Option<PathBuf>,
+    /// Bytes kept per stream (first half + last half); 0 keeps everything.
+    pub max_output_bytes: u64,
+    /// Directory to run the command in; `None` uses dt's current directory.
+    pub working_dir: Option<PathBuf>,
+    /// Record HEAD commit, branch and dirty state when run inside a git repository.
+    pub capture_git: bool,
+    /// Paths/globs (relative to the working directory) snapshotted after the run.
+    pub track: Vec<String>,
+}
+
+impl ExecOptions {
+    /// Shell program and arguments placed before `-c <command>`.
+    pub fn shell_argv(&self) -> Vec<String> {
+        if self.shell.is_empty() {
+            vec![DEFAULT_SHELL.to_string()]
+        } else {
+            self.shell.clone()
+        }
+    }
+}
+
+/// Shell used when neither `--shell` nor `run.shell` is set.
+pub const DEFAULT_SHELL: &str = "sh";
+
+/// Split a shell specification such as `bash -o pipefail` into program and
+/// arguments, honouring quotes and backslashes the way a POSIX shell would.
+pub fn parse_shell(spec: &str, i18n: &crate::i18n::I18n) -> Result<Vec<String>> {
+    shell_words::split(spec).map_err(|_| anyhow!(i18n.t_format("error_invalid_shell", &[spec])))
+}
+
+/// Stdin handling for a run.
+#[derive(Debug, Clone, Default)]
+pub enum StdinSource {
+    /// Pass dt's stdin straight through without keeping a copy.
+    #[default]
+    Inherit,
+    /// Tee dt's stdin into the command and keep what was delivered.
+    Record,
+    /// Feed previously recorded bytes instead of reading dt's stdin.
+    Replay(Vec<u8>),
+    /// Give the command an empty stdin (dt keeps the terminal for itself).
+    Null,
+}
+
+/// Result of waiting for the spawned process and draining its output.
+struct RawOutcome {
+    status: ExitStatus,
+    usage: Option<ResourceUsage>,
+    stdout: CapturedOutput,
+    stderr: CapturedOutput,
+    stdin: Option<Vec<u8>>,
+    timeline: Vec<TimelineChunk>,
+    timed_out: bool,
+}
+
+/// Bounded capture of one output stream. The first half of the budget is streamed
+/// to a staging file as it arrives (or kept in memory without a capture directory);
+/// the most recent half is kept in a ring and appended once the stream ends.
+struct OutputCapture {
+    staged: Option<(std::fs::File, tempfile::TempPath)>,
+    hasher: Sha256,
+    head: Vec<u8>,
+    head_limit: u64,
+    head_len: u64,
+    tail: VecDeque<u8>,
+    tail_limit: usize,
+    total: u64,
+}
+
+/// What was kept of one output stream.
+struct CapturedOutput {
+    bytes: Vec<u8>, // All kept bytes, or only a view of them when staged (see `view`)
+    staged: Option<tempfile::TempPath>,
+    truncation: Option<OutputTruncation>,
+    view: Option<OutputTruncation>, // How `bytes` cut down the staged stream
+    sha256: String,                 // SHA256 of all kept bytes
+}
+
+impl CapturedOutput {
+    fn empty() -> Self {
+        Self {
+            bytes: Vec::new(),
+            staged: None,
+            truncation: None,
+            view: None,
+            sha256: storage::hash_bytes(&[]),
+        }
+    }
+}
+
+impl OutputCapture {
+    fn new(options: &ExecOptions, stream: OutputStream) -> io::Result<Self> {
+        let (head_limit, tail_limit) = match options.max_output_bytes {
+            0 => (u64::MAX, 0),
+            max => {
+                let head = max.div_ceil(2);
+                (head, (max - head) as usize)
+            }
+        };
+        let staged = match &options.capture_dir {
+            Some(dir) => {
+                std::fs::create_dir_all(dir)?;
+                let prefix = match stream {
+                    OutputStream::Stdout => "stdout_",
+                    OutputStream::Stderr => "stderr_",
+                };
+                let file = tempfile::Builder::new()
+                    .prefix(prefix)
+                    .suffix(".part")
+                    .tempfile_in(dir)?;
+                // Held while the command runs; see `StoreManager::remove_stale_staging`.
+                file.as_file().lock()?;
+                Some(file.into_parts())
+            }
+            None => None,
+        };
+        Ok(Self {
+            staged,
+            hasher: Sha256::new(),
+            head: Vec::new(),
+            head_limit,
+            head_len: 0,
+            tail: VecDeque::new(),
+            tail_limit,
+            total: 0,
+        })
+    }
+
+    /// Append a chunk and return its offset in the full stream.
+    fn write(&mut self, data: &[u8]) -> io::Result<u64> {
+        let offset = self.total;
+        self.total += data.len() as u64;
+
+        let room = (self.head_limit - self.head_len).min(data.len() as u64) as usize;
+        let (head, rest) = data.split_at(room);
+        if !head.is_empty() {
+            self.hasher.update(head);
+            match &mut self.staged {
+                Some((file, _)) => file.write_all(head)?,
+                None => self.head.extend_from_slice(head),
+            }
+            self.head_len += head.len() as u64;
+        }
+        if !rest.is_empty() && self.tail_limit > 0 {
+            let keep = &rest[rest.len().saturating_sub(self.tail_limit)..];
+            self.tail.extend(keep.iter().copied());
+            let excess = self.tail.len().saturating_sub(self.tail_limit);
+            self.tail.drain(..excess);
+        }
+        Ok(offset)
+    }
+
+    fn finish(self) -> io::Result<CapturedOutput> {
+        let tail_len = self.tail.len() as u64;
+        let truncation = (self.total > self.head_len + tail_len).then_some(OutputTruncation {
+            total_bytes: self.total,
+            head_bytes: self.head_len,
+            tail_bytes: tail_len,
+        });
+        let (tail_front, tail_back) = self.tail.as_slices();
+        let mut hasher = self.hasher;
+        hasher.update(tail_front);
+        hasher.update(tail_back);
+        let sha256 = hex::encode(hasher.finalize());
+        match self.staged {
+            Some((mut file, path)) => {
+                file.write_all(tail_front)?;
+                file.write_all(tail_back)?;
+                file.flush()?;
+                drop(file);
+                // The staged file stays the source of truth; only a bounded view
+                // of it is held in memory.
+                let (bytes, view) = storage::read_view(
+                    io::BufReader::new(std::fs::File::open(&path)?),
+                    storage::MAX_VIEW_BYTES,
+                )?;
+                Ok(CapturedOutput {
+                    bytes,
+                    staged: Some(path),
+                    truncation,
+                    view,
+                    sha256,
+                })
+            }
+            None => {
+                let mut bytes = self.head;
+                bytes.extend_from_slice(tail_front);
+                bytes.extend_from_slice(tail_back);
+                Ok(CapturedOutput {
+                    bytes,
+                    staged: None,
+                    truncation,
+                    view: None,
+                    sha256,
+                })
+            }
+        }
+    }
+}
+
+/// Shared, arrival-ordered log of output chunks from the reader threads.
+#[derive(Clone)]
+struct TimelineRecorder {
+    start: Instant,
+    chunks: Arc<Mutex<Vec<TimelineChunk>>>,
+}
+
+impl TimelineRecorder {
+    fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            chunks: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    fn record(&self, stream: OutputStream, offset: u64, len: usize) {
+        // Timestamp under the lock so entries stay sorted by elapsed time.
+        let mut chunks = self.chunks.lock().unwrap();
+        chunks.push(TimelineChunk {
+            stream,
+            elapsed_us: self.start.elapsed().as_micros() as u64,
+            offset,
+            len: len as u64,
+        });
+    }
+
+    fn finish(self) -> Vec<TimelineChunk> {
+        self.chunks.lock().unwrap().clone()
+    }
+}
+
+/// Exit status and accounting collected when the child is reaped.
+struct Reaped {
+    status: ExitStatus,
+    usage: Option<ResourceUsage>,
+}
+
+#[cfg(unix)]
+fn resource_usage_from(usage: &libc::rusage) -> ResourceUsage {
+    fn timeval_ms(tv: &libc::timeval) -> u64 {
+        (tv.tv_sec as u64) * 1000 + (tv.tv_usec as u64) / 1000
+    }
+    // Linux reports ru_maxrss in kilobytes, macOS in bytes.
+    let max_rss_kb = if cfg!(target_os = "macos") {
+        usage.ru_maxrss as u64 / 1024
+    } else {
+        usage.ru_maxrss as u64
+    };
+    ResourceUsage {
+        user_cpu_ms: timeval_ms(&usage.ru_utime),
+        sys_cpu_ms: timeval_ms(&usage.ru_stime),
+        max_rss_kb,
+        voluntary_ctx_switches: usage.ru_nvcsw as u64,
+        involuntary_ctx_switches: usage.ru_nivcsw as u64,
+    }
+}
+
+/// Parse a human-friendly duration such as `500ms`, `30s`, `5m` or `1h`.
+/// A bare number is interpreted as seconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let amount: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid duration '{}'. Examples: 500ms, 30s, 5m", value))?;
+    let factor = match unit.trim() {
+        "ms" => 0.001,
+        "" | "s" | "sec" | "secs" => 1.0,
+        "m" | "min" | "mins" => 60.0,
+        "h" | "hr" | "hrs" => 3600.0,
+        other => {
+            return Err(format!(
+                "Invalid duration unit '{}'. Supported: ms, s, m, h",
+                other
+            ))
+        }
+    };
+    Duration::try_from_secs_f64(amount * factor)
+        .map_err(|_| format!("Invalid duration '{}'. Examples: 500ms, 30s, 5m", value))
+}
+
+/// Map a signal number to its conventional name (`SIGSEGV`, `SIGKILL`, ...).
+#[cfg(unix)]
+pub fn signal_name(signal: i32) -> Option<&'static str> {
+    let name = match signal {
+        libc::SIGHUP => "SIGHUP",
+        libc::SIGINT => "SIGINT",
+        libc::SIGQUIT => "SIGQUIT",
+        libc::SIGILL => "SIGILL",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGUSR1 => "SIGUSR1",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGUSR2 => "SIGUSR2",
+        libc::SIGPIPE => "SIGPIPE",
+        libc::SIGALRM => "SIGALRM",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGCHLD => "SIGCHLD",
+        libc::SIGCONT => "SIGCONT",
+        libc::SIGSTOP => "SIGSTOP",
+        libc::SIGTSTP => "SIGTSTP",
+        libc::SIGTTIN => "SIGTTIN",
+        libc::SIGTTOU => "SIGTTOU",
+        libc::SIGURG => "SIGURG",
+        libc::SIGXCPU => "SIGXCPU",
+        libc::SIGXFSZ => "SIGXFSZ",
+        libc::SIGVTALRM => "SIGVTALRM",
+        libc::SIGPROF => "SIGPROF",
+        libc::SIGWINCH => "SIGWINCH",
+        libc::SIGSYS => "SIGSYS",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Match `name` against a pattern where `*` stands for any run of characters.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if name.len() < first.len() + last.len() || !name.starts_with(first) || !name.ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Snapshot the environment variables selected by the allow/deny lists.
+/// Returns `None` when nothing is allowlisted, so old and new records read the same.
+pub fn capture_env(allowlist: &[String], denylist: &[String]) -> Option<BTreeMap<String, String>> {
+    if allowlist.is_empty() {
+        return None;
+    }
+    let captured = std::env::vars_os()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value)))
+        .filter(|(key, _)| allowlist.iter().any(|p| wildcard_match(p, key)))
+        .filter(|(key, _)| !denylist.iter().any(|p| wildcard_match(p, key)))
+        .map(|(key, value)| (key, value.to_string_lossy().into_owned()))
+        .collect();
+    Some(captured)
+}
+
+/// Format a duration compactly for messages (e.g. `1500ms`, `30s`).
+pub fn format_duration(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 && duration.as_secs() > 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
 
 pub struct CommandExecutor;
 
 impl CommandExecutor {
-    pub fn execute(command: &str, i18n: &crate::i18n::I18n) -> Result<CommandExecution> {
+    pub fn execute(
+        command: &str,
+        options: &ExecOptions,
+        i18n: &crate::i18n::I18n,
+    ) -> Result<CommandExecution> {
+        let env = capture_env(&options.env_allowlist, &options.env_denylist);
+        let working_dir = match &options.working_dir {
+            Some(dir) => dir.clone(),
+            None => std::env::current_dir()?,
+        };
+        // Taken before the command runs: builds may touch the work tree.
+        let git = if options.capture_git {
+            crate::git::capture(&working_dir)
+        } else {
+            None
+        };
         let start_time = Instant::now();
 
-        let mut child = Command::new("sh")
-            .arg("-c")
-            .arg(command)
-            .stdin(Stdio::inherit())
+        let outcome = if options.pty {
+            Self::run_pty(command, options, i18n)?
+        } else {
+            Self::run_piped(command, options, i18n)?
+        };
+
+        let duration = start_time.elapsed();
+
+        let formatted_command = storage::format_command(command);
+        let command_hash = storage::hash_command(&formatted_command);
+        let timestamp = Utc::now();
+        let record_id = format!("{}_{}", command_hash, storage::new_record_key(&timestamp));
+
+        let record = CommandRecord {
+            command: formatted_command,
+            command_hash,
+            timestamp,
+            working_dir,
+            exit_code: outcome.status.code().unwrap_or(-1),
+            duration_ms: duration.as_millis() as u64,
+            record_id,
+            short_code: None,
+            pty: options.pty,
+            termination: Self::termination_reason(&outcome),
+            signal: None,
+            signal_name: None,
+            core_dumped: false,
+            resources: outcome.usage.clone(),
+            stdout_encoding: None,
+            stderr_encoding: None,
+            stdin_hash: outcome.stdin.as_deref().map(storage::hash_bytes),
+            stdin_size: outcome.stdin.as_ref().map(|bytes| bytes.len() as u64),
+            env,
+            shell: Some(shell_words::join(options.shell_argv())),
+            shell_argv: Some(options.shell_argv()),
+            raw_command: Some(command.to_string()),
+            stdout_truncation: None,
+            stderr_truncation: None,
+            git,
+            artifacts: None,
+            host: Some(crate::host::capture()),
+            stdout_sha256: None,
+            stderr_sha256: None,
+            stdout_codec: Default::default(),
+            stderr_codec: Default::default(),
+        };
+        #[cfg(unix)]
+        let record = {
+            use std::os::unix::process::ExitStatusExt;
+            let mut record = record;
+            record.signal = outcome.status.signal();
+            record.signal_name = record.signal.and_then(signal_name).map(str::to_string);
+            record.core_dumped = outcome.status.core_dumped();
+            record
+        };
+
+        let stdout_encoding = options
+            .encoding
+            .unwrap_or_else(|| crate::encoding::detect(&outcome.stdout.bytes));
+        let stderr_encoding = options
+            .encoding
+            .unwrap_or_else(|| crate::encoding::detect(&outcome.stderr.bytes));
+        let mut record = record;
+        record.stdout_encoding = Some(stdout_encoding.name().to_string());
+        record.stderr_encoding = Some(stderr_encoding.name().to_string());
+        record.stdout_truncation = outcome.stdout.truncation;
+        record.stderr_truncation = outcome.stderr.truncation;
+        record.stdout_sha256 = Some(outcome.stdout.sha256);
+        record.stderr_sha256 = Some(outcome.stderr.sha256);
+
+        let timeline = Self::stored_timeline(
+            outcome.timeline,
+            outcome.stdout.truncation.as_ref(),
+            outcome.stderr.truncation.as_ref(),
+        );
+        let mut execution = CommandExecution {
+            record,
+            stdout: storage::decode_output(
+                &outcome.stdout.bytes,
+                outcome.stdout.view.as_ref(),
+                outcome.stdout.truncation.as_ref(),
+                |bytes| crate::encoding::decode(bytes, stdout_encoding),
+                i18n,
+            ),
+            stderr: storage::decode_output(
+                &outcome.stderr.bytes,
+                outcome.stderr.view.as_ref(),
+                outcome.stderr.truncation.as_ref(),
+                |bytes| crate::encoding::decode(bytes, stderr_encoding),
+                i18n,
+            ),
+            stdout_path: None,
+            stderr_path: None,
+            streamed_stdout: !options.quiet,
+            streamed_stderr: !options.quiet,
+            stdout_raw: outcome.stdout.bytes,
+            stderr_raw: outcome.stderr.bytes,
+            stdout_view: outcome.stdout.view,
+            stderr_view: outcome.stderr.view,
+            stdin_path: None,
+            stdin_raw: outcome.stdin,
+            timeline: Some(timeline),
+            stdout_staged: outcome.stdout.staged.map(Arc::new),
+            stderr_staged: outcome.stderr.staged.map(Arc::new),
+            artifacts_dir: None,
+            artifacts_staged: None,
+        };
+        if !options.track.is_empty() {
+            let (tracked, snapshot) = crate::artifacts::snapshot(
+                &options.track,
+                &execution.record.working_dir,
+                options.capture_dir.as_deref(),
+                i18n,
+            )?;
+            execution.record.artifacts = Some(tracked);
+            execution.artifacts_dir = Some(snapshot.path().to_path_buf());
+            execution.artifacts_staged = Some(Arc::new(snapshot));
+        }
+
+        Ok(execution)
+    }
+
+    /// Rewrite timeline offsets to point into the stored (possibly truncated) output,
+    /// dropping chunks that fell into the omitted middle.
+    fn stored_timeline(
+        timeline: Vec<TimelineChunk>,
+        stdout: Option<&OutputTruncation>,
+        stderr: Option<&OutputTruncation>,
+    ) -> Vec<TimelineChunk> {
+        if stdout.is_none() && stderr.is_none() {
+            return timeline;
+        }
+        let mut stored = Vec::with_capacity(timeline.len());
+        for chunk in timeline {
+            let truncation = match chunk.stream {
+                OutputStream::Stdout => stdout,
+                OutputStream::Stderr => stderr,
+            };
+            let Some(truncation) = truncation else {
+                stored.push(chunk);
+                continue;
+            };
+            for (offset, len) in truncation.stored_ranges(chunk.offset, chunk.len) {
+                stored.push(TimelineChunk {
+                    offset,
+                    len,
+                    ..chunk.clone()
+                });
+            }
+        }
+        stored
+    }
+
+    fn termination_reason(outcome: &RawOutcome) -> TerminationReason {
+        if outcome.timed_out {
+            return TerminationReason::TimedOut;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if outcome.status.signal().is_some() {
+                return TerminationReason::Killed;
+            }
+        }
+        TerminationReason::Completed
+    }
+
+    /// Whether the command runs in its own process group, so a timeout can signal the
+    /// whole pipeline at once. A background group is stopped (SIGTTIN) when it reads the
+    /// terminal and no longer gets the terminal's Ctrl-C, so a command reading an
+    /// interactive stdin stays in dt's group; a timeout then only signals the shell.
+    fn own_process_group(options: &ExecOptions) -> bool {
+        options.timeout.is_some()
+            && !(matches!(options.stdin, StdinSource::Inherit) && io::stdin().is_terminal())
+    }
+
+    /// Build the `<shell> -c` invocation, in its own process group when
+    /// `own_process_group` says so.
+    fn shell_command(command: &str, options: &ExecOptions) -> Command {
+        let shell = options.shell_argv();
+        let mut cmd = Command::new(&shell[0]);
+        cmd.args(&shell[1..]).arg("-c").arg(command);
+        if let Some(dir) = &options.working_dir {
+            cmd.current_dir(dir);
+        }
+        #[cfg(unix)]
+        if Self::own_process_group(options) {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        #[cfg(not(unix))]
+        let _ = options;
+        cmd
+    }
+
+    /// Wait for the child, enforcing the optional timeout with a TERM-then-KILL escalation.
+    /// Returns how the child was reaped and whether the timeout fired.
+    fn wait_child(child: &mut Child, options: &ExecOptions) -> io::Result<(Reaped, bool)> {
+        let Some(timeout) = options.timeout else {
+            return Ok((Self::reap_blocking(child)?, false));
+        };
+
+        let Some(deadline) = Instant::now().checked_add(timeout) else {
+            return Ok((Self::reap_blocking(child)?, false));
+        };
+        let poll = Duration::from_millis(20);
+        while Instant::now() < deadline {
+            if let Some(reaped) = Self::reap(child, false)? {
+                return Ok((reaped, false));
+            }
+            thread::sleep(poll);
+        }
+
+        #[cfg(unix)]
+        {
+            Self::signal_group(child, options, libc::SIGTERM);
+            let grace_deadline = Instant::now() + options.kill_grace;
+            while Instant::now() < grace_deadline && !Self::has_exited(child)? {
+                thread::sleep(poll);
+            }
+            // Also makes sure helpers left in the group do not keep pipes open. The
+            // child is not reaped yet, so its pid (the group id) cannot have been reused.
+            Self::signal_group(child, options, libc::SIGKILL);
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = child.kill();
+        }
+        Ok((Self::reap_blocking(child)?, true))
+    }
+
+    fn reap_blocking(child: &mut Child) -> io::Result<Reaped> {
+        loop {
+            if let Some(reaped) = Self::reap(child, true)? {
+                return Ok(reaped);
+            }
+        }
+    }
+
+    /// Reap the child with `wait4` so the kernel hands back its resource usage
+    /// (which includes every descendant the child itself waited for).
+    #[cfg(unix)]
+    fn reap(child: &mut Child, block: bool) -> io::Result<Option<Reaped>> {
+        use std::os::unix::process::ExitStatusExt;
+
+        let pid = child.id() as libc::pid_t;
+        let flags = if block { 0 } else { libc::WNOHANG };
+        let mut status: libc::c_int = 0;
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        loop {
+            let ret = unsafe {
+                libc::wait4(
+                    pid,
+                    std::ptr::addr_of_mut!(status),
+                    flags,
+                    std::ptr::addr_of_mut!(usage),
+                )
+            };
+            if ret == pid {
+                return Ok(Some(Reaped {
+                    status: ExitStatus::from_raw(status),
+                    usage: Some(resource_usage_from(&usage)),
+                }));
+            }
+            if ret == 0 {
+                return Ok(None);
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn reap(child: &mut Child, block: bool) -> io::Result<Option<Reaped>> {
+        let status = if block {
+            Some(child.wait()?)
+        } else {
+            child.try_wait()?
+        };
+        Ok(status.map(|status| Reaped {
+            status,
+            usage: None,
+        }))
+    }
+
+    /// Drain one output pipe on a background thread, echoing it to our own
+    /// stdout/stderr and noting each chunk on the shared timeline.
+    fn spawn_reader<R: Read + Send + 'static>(
+        mut reader: R,
+        stream: OutputStream,
+        mut capture: OutputCapture,
+        timeline: TimelineRecorder,
+        echo: bool,
+    ) -> thread::JoinHandle<Result<CapturedOutput>> {
+        thread::spawn(move || -> Result<CapturedOutput> {
+            let mut buffer = [0u8; 4096];
+            loop {
+                let bytes_read = reader.read(&mut buffer)?;
+                if bytes_read == 0 {
+                    break;
+                }
+                let offset = capture.write(&buffer[..bytes_read])?;
+                timeline.record(stream, offset, bytes_read);
+                if !echo {
+                    continue;
+                }
+                match stream {
+                    OutputStream::Stdout => {
+                        let mut handle = io::stdout();
+                        handle.write_all(&buffer[..bytes_read])?;
+                        handle.flush()?;
+                    }
+                    OutputStream::Stderr => {
+                        let mut handle = io::stderr();
+                        handle.write_all(&buffer[..bytes_read])?;
+                        handle.flush()?;
+                    }
+                }
+            }
+            Ok(capture.finish()?)
+        })
+    }
+
+    fn stdin_stdio(options: &ExecOptions) -> Stdio {
+        match options.stdin {
+            StdinSource::Inherit => Stdio::inherit(),
+            StdinSource::Null => Stdio::null(),
+            StdinSource::Record | StdinSource::Replay(_) => Stdio::piped(),
+        }
+    }
+
+    /// Start feeding the child's stdin. Returns a buffer holding every byte handed to
+    /// the child; bytes are appended before they are written, so a snapshot taken after
+    /// the child exits covers everything it could have read.
+    fn start_stdin_feeder(child: &mut Child, options: &ExecOptions) -> Option<Arc<Mutex<Vec<u8>>>> {
+        let mut sink = child.stdin.take()?;
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        match &options.stdin {
+            StdinSource::Inherit | StdinSource::Null => return None,
+            StdinSource::Replay(bytes) => {
+                captured.lock().unwrap().extend_from_slice(bytes);
+                let bytes = bytes.clone();
+                thread::spawn(move || {
+                    // A command that stops reading early closes the pipe; that is not an error.
+                    let _ = sink.write_all(&bytes);
+                });
+            }
+            StdinSource::Record => {
+                let buffer = Arc::clone(&captured);
+                // Not joined: a reader blocked on an idle stdin must not hold up the run.
+                thread::spawn(move || {
+                    let mut source = io::stdin();
+                    let mut chunk = [0u8; 4096];
+                    loop {
+                        let n = match source.read(&mut chunk) {
+                            Ok(0) => break,
+                            Ok(n) => n,
+                            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                            Err(_) => break,
+                        };
+                        buffer.lock().unwrap().extend_from_slice(&chunk[..n]);
+                        if sink.write_all(&chunk[..n]).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        }
+        Some(captured)
+    }
+
+    fn captured_stdin(capture: Option<Arc<Mutex<Vec<u8>>>>) -> Option<Vec<u8>> {
+        capture.map(|buffer| buffer.lock().unwrap().clone())
+    }
+
+    /// Whether the child has exited, leaving it unreaped (`WNOWAIT`).
+    #[cfg(unix)]
+    fn has_exited(child: &Child) -> io::Result<bool> {
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        loop {
+            let ret = unsafe {
+                libc::waitid(
+                    libc::P_PID,
+                    child.id() as libc::id_t,
+                    &mut info,
+                    libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+                )
+            };
+            if ret == 0 {
+                // si_pid stays 0 while the child is still running.
+                return Ok(unsafe { info.si_pid() } != 0);
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+
+    /// Signal the child's process group, or only the child when it shares dt's group.
+    #[cfg(unix)]
+    fn signal_group(child: &Child, options: &ExecOptions, signal: libc::c_int) {
+        let pid = child.id() as libc::pid_t;
+        let target = if Self::own_process_group(options) {
+            -pid
+        } else {
+            pid
+        };
+        unsafe {
+            libc::kill(target, signal);
+        }
+    }
+
+    fn run_piped(
+        command: &str,
+        options: &ExecOptions,
+        i18n: &crate::i18n::I18n,
+    ) -> Result<RawOutcome> {
+        let stdout_capture = OutputCapture::new(options, OutputStream::Stdout)
+            .context(i18n.t("error_stage_output"))?;
+        let stderr_capture = OutputCapture::new(options, OutputStream::Stderr)
+            .context(i18n.t("error_stage_output"))?;
+        let mut child = Self::shell_command(command, options)
+            .stdin(Self::stdin_stdio(options))
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
             .context(i18n.t("error_execute_command"))?;
+        let stdin_capture = Self::start_stdin_feeder(&mut child, options);
+        let timeline = TimelineRecorder::new();
 
         let stdout = child
             .stdout
@@ -30,82 +864,368 @@ impl CommandExecutor {
             .take()
             .ok_or_else(|| anyhow!(i18n.t("error_read_stderr")))?;
 
-        let stdout_handle = thread::spawn(move || -> Result<Vec<u8>> {
-            let mut reader = stdout;
-            let mut buffer = [0u8; 4096];
-            let mut collected = Vec::new();
-            loop {
-                let bytes_read = reader.read(&mut buffer)?;
-                if bytes_read == 0 {
-                    break;
-                }
-                collected.extend_from_slice(&buffer[..bytes_read]);
-                {
-                    let mut handle = io::stdout();
-                    handle.write_all(&buffer[..bytes_read])?;
-                    handle.flush()?;
-                }
-            }
-            Ok(collected)
-        });
+        let stdout_handle = Self::spawn_reader(
+            stdout,
+            OutputStream::Stdout,
+            stdout_capture,
+            timeline.clone(),
+            !options.quiet,
+        );
+        let stderr_handle = Self::spawn_reader(
+            stderr,
+            OutputStream::Stderr,
+            stderr_capture,
+            timeline.clone(),
+            !options.quiet,
+        );
+
+        let (reaped, timed_out) =
+            Self::wait_child(&mut child, options).context(i18n.t("error_execute_command"))?;
+
+        let stdout_output = stdout_handle
+            .join()
+            .map_err(|_| anyhow!(i18n.t("error_read_stdout")))??;
+        let stderr_output = stderr_handle
+            .join()
+            .map_err(|_| anyhow!(i18n.t("error_read_stderr")))??;
+
+        Ok(RawOutcome {
+            status: reaped.status,
+            usage: reaped.usage,
+            stdout: stdout_output,
+            stderr: stderr_output,
+            stdin: Self::captured_stdin(stdin_capture),
+            timeline: timeline.finish(),
+            timed_out,
+        })
+    }
+
+    /// Run the command with stdout/stderr attached to a pseudo-terminal.
+    /// Both streams share the terminal, so the raw byte stream is recorded as stdout.
+    #[cfg(unix)]
+    fn run_pty(
+        command: &str,
+        options: &ExecOptions,
+        i18n: &crate::i18n::I18n,
+    ) -> Result<RawOutcome> {
+        let mut capture = OutputCapture::new(options, OutputStream::Stdout)
+            .context(i18n.t("error_stage_output"))?;
+        let (master, slave) = Self::open_pty().context(i18n.t("error_open_pty"))?;
+        let slave_err = slave.try_clone().context(i18n.t("error_open_pty"))?;
 
-        let stderr_handle = thread::spawn(move || -> Result<Vec<u8>> {
-            let mut reader = stderr;
+        let mut cmd = Self::shell_command(command, options);
+        cmd.stdin(Self::stdin_stdio(options))
+            .stdout(Stdio::from(slave))
+            .stderr(Stdio::from(slave_err));
+        let mut child = cmd.spawn().context(i18n.t("error_execute_command"))?;
+        let stdin_capture = Self::start_stdin_feeder(&mut child, options);
+        // Drop our copies of the slave side so reads on the master end once the child exits.
+        drop(cmd);
+
+        let timeline = TimelineRecorder::new();
+        let pty_timeline = timeline.clone();
+        let echo = !options.quiet;
+        let reader_handle = thread::spawn(move || -> Result<CapturedOutput> {
+            let mut reader = master;
             let mut buffer = [0u8; 4096];
-            let mut collected = Vec::new();
             loop {
-                let bytes_read = reader.read(&mut buffer)?;
+                let bytes_read = match reader.read(&mut buffer) {
+                    Ok(n) => n,
+                    // Linux reports EIO on the master once every slave descriptor is closed.
+                    Err(e) if e.raw_os_error() == Some(libc::EIO) => 0,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e.into()),
+                };
                 if bytes_read == 0 {
                     break;
                 }
-                collected.extend_from_slice(&buffer[..bytes_read]);
-                {
-                    let mut handle = io::stderr();
+                let offset = capture.write(&buffer[..bytes_read])?;
+                pty_timeline.record(OutputStream::Stdout, offset, bytes_read);
+                if echo {
+                    let mut handle = io::stdout();
                     handle.write_all(&buffer[..bytes_read])?;
                     handle.flush()?;
                 }
             }
-            Ok(collected)
+            Ok(capture.finish()?)
         });
 
-        let status = child.wait().context(i18n.t("error_execute_command"))?;
+        let (reaped, timed_out) =
+            Self::wait_child(&mut child, options).context(i18n.t("error_execute_command"))?;
 
-        let stdout_bytes = stdout_handle
+        let stdout_output = reader_handle
             .join()
             .map_err(|_| anyhow!(i18n.t("error_read_stdout")))??;
-        let stderr_bytes = stderr_handle
-            .join()
-            .map_err(|_| anyhow!(i18n.t("error_read_stderr")))??;
 
-        let duration = start_time.elapsed();
+        Ok(RawOutcome {
+            status: reaped.status,
+            usage: reaped.usage,
+            stdout: stdout_output,
+            stderr: CapturedOutput::empty(),
+            stdin: Self::captured_stdin(stdin_capture),
+            timeline: timeline.finish(),
+            timed_out,
+        })
+    }
 
-        let working_dir = std::env::current_dir()?;
-        let formatted_command = storage::format_command(command);
-        let command_hash = storage::hash_command(&formatted_command);
-        let timestamp = Utc::now();
-        let record_id = format!("{}_{}", command_hash, timestamp.timestamp());
+    #[cfg(not(unix))]
+    fn run_pty(
+        _command: &str,
+        _options: &ExecOptions,
+        i18n: &crate::i18n::I18n,
+    ) -> Result<RawOutcome> {
+        Err(anyhow!(i18n.t("error_pty_unsupported")))
+    }
 
-        let record = CommandRecord {
-            command: formatted_command,
-            command_hash,
-            timestamp,
-            working_dir,
-            exit_code: status.code().unwrap_or(-1),
-            duration_ms: duration.as_millis() as u64,
-            record_id,
-            short_code: None,
+    /// Open a master/slave pseudo-terminal pair sized like the current terminal.
+    #[cfg(unix)]
+    fn open_pty() -> io::Result<(std::fs::File, std::fs::File)> {
+        use std::os::fd::FromRawFd;
+
+        let mut size = libc::winsize {
+            ws_row: 24,
+            ws_col: 80,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
         };
+        unsafe {
+            let mut current: libc::winsize = std::mem::zeroed();
+            if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut current) == 0
+                && current.ws_col > 0
+                && current.ws_row > 0
+            {
+                size = current;
+            }
+        }
 
-        let execution = CommandExecution {
-            record,
-            stdout: String::from_utf8_lossy(&stdout_bytes).to_string(),
-            stderr: String::from_utf8_lossy(&stderr_bytes).to_string(),
-            stdout_path: None,
-            stderr_path: None,
-            streamed_stdout: true,
-            streamed_stderr: true,
+        let mut master: libc::c_int = -1;
+        let mut slave: libc::c_int = -1;
+        let rc = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::addr_of_mut!(size),
+            )
         };
+        if rc != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: openpty succeeded, so both descriptors are valid and owned by us.
+        let (master, slave) = unsafe {
+            (
+                std::fs::File::from_raw_fd(master),
+                std::fs::File::from_raw_fd(slave),
+            )
+        };
+        Ok((master, slave))
+    }
+}
 
-        Ok(execution)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_patterns() {
+        assert!(wildcard_match("PATH", "PATH"));
+        assert!(!wildcard_match("PATH", "MANPATH"));
+        assert!(wildcard_match("LC_*", "LC_ALL"));
+        assert!(wildcard_match("*TOKEN*", "GITHUB_TOKEN"));
+        assert!(wildcard_match("*TOKEN*", "TOKENIZER"));
+        assert!(wildcard_match("*_KEY", "AWS_SECRET_KEY"));
+        assert!(!wildcard_match("*_KEY", "KEYRING"));
+        assert!(wildcard_match("A*B*C", "AxxBxxC"));
+        assert!(!wildcard_match("A*B*C", "AC"));
+        assert!(wildcard_match("*", "ANYTHING"));
+    }
+
+    #[test]
+    fn parse_shell_honours_quotes() {
+        let i18n = crate::i18n::I18n::new("en");
+        assert_eq!(
+            parse_shell("env 'A=1 2' bash -o pipefail", &i18n).unwrap(),
+            vec!["env", "A=1 2", "bash", "-o", "pipefail"]
+        );
+        assert!(parse_shell("bash -c 'oops", &i18n).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn custom_shell_is_used_and_recorded() {
+        let i18n = crate::i18n::I18n::new("en");
+        let options = ExecOptions {
+            shell: parse_shell("bash -o pipefail", &i18n).unwrap(),
+            ..Default::default()
+        };
+        let execution = CommandExecutor::execute("false | true", &options, &i18n).unwrap();
+        assert_eq!(execution.record.exit_code, 1);
+        assert_eq!(execution.record.shell.as_deref(), Some("bash -o pipefail"));
+        assert_eq!(
+            execution.record.shell_argv,
+            Some(vec!["bash".into(), "-o".into(), "pipefail".into()])
+        );
+
+        let execution =
+            CommandExecutor::execute("false | true", &ExecOptions::default(), &i18n).unwrap();
+        assert_eq!(execution.record.exit_code, 0);
+        assert_eq!(execution.record.shell.as_deref(), Some("sh"));
+    }
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+        assert_eq!(parse_duration("2").unwrap(), Duration::from_secs(2));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert!(parse_duration("abc").is_err());
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("99999999999999999999999h").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timeout_terminates_command() {
+        let i18n = crate::i18n::I18n::new("en");
+        let options = ExecOptions {
+            timeout: Some(Duration::from_millis(200)),
+            kill_grace: Duration::from_secs(1),
+            // Not the terminal, so the pipeline gets its own process group
+            stdin: StdinSource::Null,
+            ..Default::default()
+        };
+        let started = Instant::now();
+        let execution = CommandExecutor::execute("sleep 5 | cat", &options, &i18n).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(4));
+        assert_eq!(execution.record.termination, TerminationReason::TimedOut);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn signal_is_recorded() {
+        let i18n = crate::i18n::I18n::new("en");
+        let execution =
+            CommandExecutor::execute("kill -USR1 $$", &ExecOptions::default(), &i18n).unwrap();
+        assert_eq!(execution.record.exit_code, -1);
+        assert_eq!(execution.record.signal, Some(libc::SIGUSR1));
+        assert_eq!(execution.record.signal_name.as_deref(), Some("SIGUSR1"));
+        assert_eq!(execution.record.termination, TerminationReason::Killed);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_output_keeps_raw_bytes() {
+        let i18n = crate::i18n::I18n::new("en");
+        let execution = CommandExecutor::execute(
+            "printf 'caf\\351 cr\\350me br\\373l\\351e\\n'",
+            &ExecOptions::default(),
+            &i18n,
+        )
+        .unwrap();
+        assert_eq!(execution.stdout_raw, b"caf\xe9 cr\xe8me br\xfbl\xe9e\n");
+        assert_ne!(execution.record.stdout_encoding.as_deref(), Some("UTF-8"));
+        assert_eq!(execution.stdout, "café crème brûlée\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn large_output_is_staged_and_truncated() {
+        let i18n = crate::i18n::I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let options = ExecOptions {
+            quiet: true,
+            capture_dir: Some(dir.path().to_path_buf()),
+            max_output_bytes: 20,
+            ..Default::default()
+        };
+        let execution = CommandExecutor::execute("seq 1 1000", &options, &i18n).unwrap();
+
+        assert_eq!(execution.stdout_raw, b"1\n2\n3\n4\n5\n\n999\n1000\n");
+        let truncation = execution.record.stdout_truncation.unwrap();
+        assert_eq!(truncation.total_bytes, 3893);
+        assert_eq!((truncation.head_bytes, truncation.tail_bytes), (10, 10));
+        assert!(execution.stdout.contains("5\n[... 3873 bytes omitted"));
+        assert!(execution.record.stderr_truncation.is_none());
+
+        let staged = execution.stdout_staged.as_ref().unwrap();
+        assert_eq!(
+            std::fs::read(staged.as_ref() as &std::path::Path).unwrap(),
+            execution.stdout_raw
+        );
+        let stored: u64 = execution
+            .timeline
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|chunk| chunk.len)
+            .sum();
+        assert_eq!(stored, 20);
+
+        // Staged files are removed once nothing refers to the run any more.
+        drop(execution);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn replayed_stdin_is_fed_and_recorded() {
+        let i18n = crate::i18n::I18n::new("en");
+        let options = ExecOptions {
+            stdin: StdinSource::Replay(b"b\na\n".to_vec()),
+            ..Default::default()
+        };
+        let execution = CommandExecutor::execute("sort", &options, &i18n).unwrap();
+        assert_eq!(execution.stdout, "a\nb\n");
+        assert_eq!(execution.stdin_raw.as_deref(), Some(&b"b\na\n"[..]));
+        assert_eq!(execution.record.stdin_size, Some(4));
+        assert_eq!(
+            execution.record.stdin_hash.as_deref(),
+            Some(storage::hash_bytes(b"b\na\n").as_str())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timeline_keeps_stdout_stderr_order() {
+        let i18n = crate::i18n::I18n::new("en");
+        let execution = CommandExecutor::execute(
+            "echo one; sleep 0.05; echo two >&2; sleep 0.05; echo three",
+            &ExecOptions::default(),
+            &i18n,
+        )
+        .unwrap();
+        let timeline = execution.timeline.as_ref().unwrap();
+        let streams: Vec<OutputStream> = timeline.iter().map(|c| c.stream).collect();
+        assert_eq!(
+            streams,
+            vec![
+                OutputStream::Stdout,
+                OutputStream::Stderr,
+                OutputStream::Stdout
+            ]
+        );
+        assert_eq!(
+            execution.combined_output().as_deref(),
+            Some("one\ntwo\nthree\n")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resource_usage_is_recorded() {
+        let i18n = crate::i18n::I18n::new("en");
+        let execution = CommandExecutor::execute(
+            "i=0; while [ $i -lt 20000 ]; do i=$((i+1)); done",
+            &ExecOptions::default(),
+            &i18n,
+        )
+        .unwrap();
+        let usage = execution.record.resources.expect("rusage captured");
+        assert_eq!(execution.record.stdout_encoding.as_deref(), Some("UTF-8"));
+        assert!(usage.max_rss_kb > 0);
+        assert!(usage.user_cpu_ms + usage.sys_cpu_ms > 0);
     }
 }
@@ -0,0 +1,72 @@
+use crate::storage::GitInfo;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// HEAD commit, branch and dirty state of the repository containing `dir`.
+/// Returns `None` outside a work tree, before the first commit, or when git
+/// is not installed.
+pub fn capture(dir: &Path) -> Option<GitInfo> {
+    let commit = git(dir, &["rev-parse", "--verify", "-q", "HEAD"])?;
+    let branch = git(dir, &["symbolic-ref", "--short", "-q", "HEAD"]);
+    let dirty = git(dir, &["status", "--porcelain"]).is_some_and(|status| !status.is_empty());
+    Some(GitInfo {
+        commit,
+        branch,
+        dirty,
+    })
+}
+
+/// Trimmed stdout of a successful git command run in `dir`.
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        // `git status` would otherwise refresh the index and race with the user's git.
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn capture_reports_commit_branch_and_dirty_state() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(capture(dir.path()), None);
+        let run = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(["-c", "user.name=dt", "-c", "user.email=dt@example.com"])
+                .args(args)
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false)
+        };
+        if !run(&["init", "-q", "-b", "main"]) {
+            // git is not installed
+            return;
+        }
+        fs::write(dir.path().join("a.txt"), "a\n").unwrap();
+        assert!(run(&["add", "a.txt"]));
+        assert!(run(&["commit", "-q", "-m", "init"]));
+
+        let info = capture(dir.path()).unwrap();
+        assert_eq!(info.commit.len(), 40);
+        assert_eq!(info.branch.as_deref(), Some("main"));
+        assert!(!info.dirty);
+
+        fs::write(dir.path().join("a.txt"), "b\n").unwrap();
+        assert!(capture(dir.path()).unwrap().dirty);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::storage::HostInfo;
+
+/// Machine, user, OS and dt version the current process runs as.
+pub fn capture() -> HostInfo {
+    HostInfo {
+        hostname: hostname().unwrap_or_else(|| "unknown".to_string()),
+        user: ["USER", "LOGNAME", "USERNAME"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty())),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        kernel: kernel_release(),
+        dt_version: env!("CARGO_PKG_VERSION").to_string(),
+    }
+}
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: the buffer is valid for its full length; a truncated name may
+    // lack the terminator, so the last byte is kept as NUL.
+    let rc = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len() - 1) };
+    if rc != 0 {
+        return None;
+    }
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    let name = String::from_utf8_lossy(&buf[..end]).into_owned();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(not(unix))]
+fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok().filter(|v| !v.is_empty())
+}
+
+#[cfg(unix)]
+fn kernel_release() -> Option<String> {
+    // SAFETY: utsname is plain data and uname only writes into it.
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return None;
+    }
+    let release: Vec<u8> = uts
+        .release
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as u8)
+        .collect();
+    Some(String::from_utf8_lossy(&release).into_owned())
+}
+
+#[cfg(not(unix))]
+fn kernel_release() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_describes_this_machine() {
+        let host = capture();
+        assert!(!host.hostname.is_empty());
+        assert!(host.os.starts_with(std::env::consts::OS));
+        assert_eq!(host.dt_version, env!("CARGO_PKG_VERSION"));
+        #[cfg(unix)]
+        assert!(host.kernel.is_some_and(|k| !k.is_empty()));
+    }
+}
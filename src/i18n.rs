@@ -28,6 +28,46 @@ impl I18n {
             "help_run_diff_with".to_string(),
             "Diff with 'first', 'last', or a specific short code after run".to_string(),
         );
+        en.insert(
+            "help_run_pty".to_string(),
+            "Run under a pseudo-terminal so tools see a TTY (records raw terminal output)"
+                .to_string(),
+        );
+        en.insert("help_run_timeout".to_string(), "Stop the command after DURATION (e.g. 30s, 5m); TERM first, KILL after the grace period".to_string());
+        en.insert(
+            "help_run_record_stdin".to_string(),
+            "Save a copy of stdin with the record".to_string(),
+        );
+        en.insert(
+            "help_run_stdin_from".to_string(),
+            "Feed stdin recorded by an earlier run ('last' or a short code)".to_string(),
+        );
+        en.insert(
+            "help_run_shell".to_string(),
+            "Shell to run the command with, e.g. \"bash -o pipefail\" (default: run.shell)"
+                .to_string(),
+        );
+        en.insert(
+            "help_config_run_pty".to_string(),
+            "run.pty: true | false (capture through a pseudo-terminal by default; default: false)"
+                .to_string(),
+        );
+        en.insert(
+            "help_config_run_kill_grace".to_string(),
+            "run.kill_grace_secs: seconds between SIGTERM and SIGKILL on timeout (default: 5)"
+                .to_string(),
+        );
+        en.insert("help_config_run_env".to_string(), "run.env_allowlist / run.env_denylist: environment variables recorded with each run (`*` wildcard; denylist wins)".to_string());
+        en.insert(
+            "help_config_run_shell".to_string(),
+            "run.shell: shell and arguments used by dt run (default: \"sh\")".to_string(),
+        );
+        en.insert(
+            "help_config_run_batch_jobs".to_string(),
+            "run.batch_jobs: commands dt run-batch runs at once (default: 4)".to_string(),
+        );
+        en.insert("help_config_run_capture_git".to_string(), "run.capture_git: record HEAD commit, branch and dirty state inside git repositories (default: true)".to_string());
+        en.insert("help_config_storage_compression".to_string(), "storage.compression / compression_threshold: compress stored stdout/stderr of at least this many bytes with none, gzip or zstd (default: none, 65536)".to_string());
         en.insert(
             "help_diff".to_string(),
             "Compare command output differences".to_string(),
@@ -36,6 +76,206 @@ impl I18n {
             "help_show".to_string(),
             "Show stdout/stderr of a selected execution".to_string(),
         );
+        en.insert(
+            "help_replay".to_string(),
+            "Play back a recorded run with its original output timing".to_string(),
+        );
+        en.insert(
+            "help_replay_code".to_string(),
+            "Replay the run with this short code (requires COMMAND)".to_string(),
+        );
+        en.insert(
+            "help_replay_speed".to_string(),
+            "Playback speed, e.g. 2x or 0.5x (default: 1x)".to_string(),
+        );
+        en.insert(
+            "replay_no_timeline".to_string(),
+            "This run has no recorded timing; printing its output at once".to_string(),
+        );
+        en.insert(
+            "help_rerun".to_string(),
+            "Re-execute a recorded run in its original directory and diff against it".to_string(),
+        );
+        en.insert(
+            "help_rerun_target".to_string(),
+            "Short code or record id of the run; omit to pick one interactively".to_string(),
+        );
+        en.insert(
+            "help_run_batch".to_string(),
+            "Run every command in a file and summarize which ones changed".to_string(),
+        );
+        en.insert("help_run_batch_file".to_string(), "Commands, one per line (# comments), or a .toml file with a [[commands]] list of command/name".to_string());
+        en.insert(
+            "help_run_batch_jobs".to_string(),
+            "How many commands run at once (default: run.batch_jobs)".to_string(),
+        );
+        en.insert(
+            "help_run_batch_timeout".to_string(),
+            "Stop each command after this long, e.g. 30s".to_string(),
+        );
+        en.insert(
+            "batch_header".to_string(),
+            "Running {0} command(s), {1} at a time".to_string(),
+        );
+        en.insert(
+            "batch_progress".to_string(),
+            "{0}: exit {1} in {2}ms ({3})".to_string(),
+        );
+        en.insert(
+            "batch_failed_to_run".to_string(),
+            "{0}: failed to run: {1}".to_string(),
+        );
+        en.insert("batch_change_new".to_string(), "new".to_string());
+        en.insert(
+            "batch_change_unchanged".to_string(),
+            "unchanged".to_string(),
+        );
+        en.insert(
+            "batch_change_output".to_string(),
+            "output changed".to_string(),
+        );
+        en.insert(
+            "batch_change_exit".to_string(),
+            "exit {0} → {1}".to_string(),
+        );
+        en.insert("batch_change_error".to_string(), "not run".to_string());
+        en.insert("batch_col_command".to_string(), "COMMAND".to_string());
+        en.insert("batch_col_exit".to_string(), "EXIT".to_string());
+        en.insert("batch_col_change".to_string(), "CHANGE".to_string());
+        en.insert("batch_col_code".to_string(), "CODE".to_string());
+        en.insert("batch_col_time".to_string(), "TIME".to_string());
+        en.insert(
+            "batch_totals".to_string(),
+            "{0} command(s): {1} changed, {2} new, {3} unchanged, {4} failed".to_string(),
+        );
+        en.insert(
+            "batch_file_read".to_string(),
+            "Failed to read batch file {0}: {1}".to_string(),
+        );
+        en.insert(
+            "batch_bad_toml".to_string(),
+            "Invalid batch file {0}: {1}".to_string(),
+        );
+        en.insert(
+            "batch_empty".to_string(),
+            "No commands found in {0}".to_string(),
+        );
+        en.insert(
+            "rerun_header".to_string(),
+            "Re-running `{0}` (code {1}) in {2}".to_string(),
+        );
+        en.insert(
+            "rerun_target_not_found".to_string(),
+            "No recorded run with short code or record id '{0}'".to_string(),
+        );
+        en.insert(
+            "rerun_missing_dir".to_string(),
+            "Recorded working directory {0} no longer exists".to_string(),
+        );
+        en.insert(
+            "help_watch".to_string(),
+            "Re-run a command periodically and highlight changed lines".to_string(),
+        );
+        en.insert(
+            "help_watch_interval".to_string(),
+            "Time between runs, e.g. 5s or 500ms (default: 2s)".to_string(),
+        );
+        en.insert(
+            "help_watch_changes_only".to_string(),
+            "Only save runs whose output differs from the last saved run".to_string(),
+        );
+        en.insert(
+            "help_watch_count".to_string(),
+            "Stop after N runs".to_string(),
+        );
+        en.insert("watch_header".to_string(), "Every {0}: {1}".to_string());
+        en.insert(
+            "watch_unchanged_not_saved".to_string(),
+            "(unchanged, not saved)".to_string(),
+        );
+        en.insert(
+            "watch_waiting".to_string(),
+            "Waiting for the first run...".to_string(),
+        );
+        en.insert(
+            "watch_footer".to_string(),
+            "runs: {0}  saved: {1}  |  q quit  ↑/↓ PgUp/PgDn scroll".to_string(),
+        );
+        en.insert("watch_finished".to_string(), "(finished)".to_string());
+        en.insert(
+            "on_change_watching".to_string(),
+            "Watching {0} for changes (Ctrl+C to stop)...".to_string(),
+        );
+        en.insert(
+            "on_change_triggered".to_string(),
+            "Changed: {0} — re-running".to_string(),
+        );
+        en.insert(
+            "on_change_triggered_many".to_string(),
+            "Changed: {0} and {1} more — re-running".to_string(),
+        );
+        en.insert(
+            "on_change_path_missing".to_string(),
+            "--on-change: '{0}' does not exist".to_string(),
+        );
+        en.insert(
+            "on_change_bad_glob".to_string(),
+            "--on-change: invalid glob '{0}': {1}".to_string(),
+        );
+        en.insert(
+            "help_run_on_change".to_string(),
+            "Re-run whenever these paths/globs change (comma-separated or repeated)".to_string(),
+        );
+        en.insert(
+            "help_run_debounce".to_string(),
+            "Quiet period after the last change before re-running (default: 300ms)".to_string(),
+        );
+        en.insert(
+            "help_run_repeat".to_string(),
+            "Run N times and summarize distinct output variants (flakiness check)".to_string(),
+        );
+        en.insert(
+            "help_run_quiet".to_string(),
+            "Record silently: no live output, banners, summary or diff".to_string(),
+        );
+        en.insert(
+            "help_run_no_stream".to_string(),
+            "Don't echo output live; print it after the command finishes".to_string(),
+        );
+        en.insert(
+            "help_run_json".to_string(),
+            "Print one JSON summary (record_id, short_code, exit_code, duration, output hashes)"
+                .to_string(),
+        );
+        en.insert(
+            "help_run_exit_zero".to_string(),
+            "Exit 0 once recorded instead of passing on the command's exit status".to_string(),
+        );
+        en.insert(
+            "help_run_diff_exit_code".to_string(),
+            "With -d, --repeat or --on-change: exit with STATUS (default 3) when the output differs".to_string(),
+        );
+        en.insert("help_run_track".to_string(), "Snapshot these files, directories or globs (comma-separated, relative to the working directory) after the run and diff them too".to_string());
+        en.insert(
+            "repeat_run_line".to_string(),
+            "Run {0}/{1}: exit={2} {3}ms [code:{4}]".to_string(),
+        );
+        en.insert(
+            "repeat_summary".to_string(),
+            "{0} runs, {1} distinct output variant(s)".to_string(),
+        );
+        en.insert(
+            "repeat_deterministic".to_string(),
+            "All runs produced identical output and exit code: deterministic".to_string(),
+        );
+        en.insert(
+            "repeat_variant_line".to_string(),
+            "Variant {0}: {1} run(s), exit={2}, codes: {3}".to_string(),
+        );
+        en.insert(
+            "repeat_variant_diff".to_string(),
+            "━━━ Variant {0} vs most common variant ━━━".to_string(),
+        );
         en.insert(
             "help_show_command".to_string(),
             "Command and arguments to show (quote piped expressions)".to_string(),
@@ -118,6 +358,22 @@ impl I18n {
             "Optional query to filter (substring/subsequence)".to_string(),
         );
         en.insert("help_ls_json".to_string(), "Output JSON".to_string());
+        en.insert(
+            "help_ls_signal".to_string(),
+            "Only runs terminated by a signal (name, number, or 'any')".to_string(),
+        );
+        en.insert(
+            "help_ls_commit".to_string(),
+            "Only runs recorded at a commit starting with this hash prefix".to_string(),
+        );
+        en.insert(
+            "help_ls_branch".to_string(),
+            "Only runs recorded on this branch".to_string(),
+        );
+        en.insert(
+            "help_ls_host".to_string(),
+            "Only show runs recorded on this host (full or short hostname)".to_string(),
+        );
         en.insert(
             "help_parse".to_string(),
             "Parse a Bash snippet/file to AST (tree-sitter-bash)".to_string(),
@@ -191,6 +447,14 @@ impl I18n {
             "command_completed".to_string(),
             "Command completed, exit code: {0}".to_string(),
         );
+        en.insert(
+            "run_timed_out".to_string(),
+            "Command timed out after {0} and was terminated".to_string(),
+        );
+        en.insert(
+            "run_terminated_by_signal".to_string(),
+            "Command terminated by {0}".to_string(),
+        );
         en.insert("execution_time".to_string(), "Execution time".to_string());
         en.insert("stdout".to_string(), "Standard output:".to_string());
         en.insert("stderr".to_string(), "Error output:".to_string());
@@ -288,7 +552,7 @@ impl I18n {
         );
         en.insert(
             "status_nav_compact".to_string(),
-            "Sel: type/jk, PgUp/PgDn or ^f/^b page, Space/Enter toggle, Ctrl+X del (confirm) Tab→Preview | Prev: jk return, Shift/Ctrl+↑↓ scroll, Enter diff, q back, Q exit".to_string(),
+            "Sel: type/jk, PgUp/PgDn or ^f/^b page, Space/Enter toggle, Ctrl+X del (confirm), Ctrl+R rerun, Tab→Preview | Prev: jk return, Shift/Ctrl+↑↓ scroll, Enter diff, q back, Q exit".to_string(),
         );
         en.insert(
             "status_nav_compact_show".to_string(),
@@ -314,6 +578,14 @@ impl I18n {
             "delete_success_status".to_string(),
             "Deleted run from {0}.".to_string(),
         );
+        en.insert(
+            "rerun_success_status".to_string(),
+            "Re-ran and saved as code {0}.".to_string(),
+        );
+        en.insert(
+            "rerun_failed_status".to_string(),
+            "Re-run failed: {0}".to_string(),
+        );
         en.insert(
             "delete_failed_status".to_string(),
             "Failed to delete run: {0}".to_string(),
@@ -381,15 +653,108 @@ impl I18n {
             "diff_exit_code".to_string(),
             "exit code: {0} -> {1}".to_string(),
         );
+        en.insert(
+            "diff_capture_mode".to_string(),
+            "capture mode: {0} -> {1}".to_string(),
+        );
+        en.insert("diff_shell".to_string(), "shell: {0} -> {1}".to_string());
+        en.insert(
+            "diff_git_commit".to_string(),
+            "commit: {0} -> {1}".to_string(),
+        );
+        en.insert("git_changed".to_string(), "Git state changed:".to_string());
+        en.insert("host_label".to_string(), "host".to_string());
+        en.insert("diff_host".to_string(), "host: {0} -> {1}".to_string());
+        en.insert("host_changed".to_string(), "Host changed:".to_string());
+        en.insert(
+            "tracked_files_diff".to_string(),
+            "Tracked files:".to_string(),
+        );
+        en.insert(
+            "tracked_files_unchanged".to_string(),
+            "{0} tracked file(s) unchanged".to_string(),
+        );
+        en.insert("tracked_file_modified".to_string(), "~ {0}".to_string());
+        en.insert(
+            "tracked_file_added".to_string(),
+            "+ {0} (new, {1} bytes)".to_string(),
+        );
+        en.insert(
+            "tracked_file_removed".to_string(),
+            "- {0} (removed)".to_string(),
+        );
+        en.insert(
+            "tracked_file_binary".to_string(),
+            "  binary file changed: {0} -> {1} bytes".to_string(),
+        );
+        en.insert(
+            "git_none".to_string(),
+            "not in a git repository".to_string(),
+        );
+        en.insert(
+            "diff_termination".to_string(),
+            "termination: {0} -> {1}".to_string(),
+        );
+        en.insert(
+            "output_truncated_marker".to_string(),
+            "[... {0} bytes omitted (storage.max_output_bytes) ...]".to_string(),
+        );
+        en.insert(
+            "output_view_marker".to_string(),
+            "[... {0} bytes not shown; the full output is stored ...]".to_string(),
+        );
+        en.insert(
+            "error_stage_output".to_string(),
+            "Failed to create output staging file".to_string(),
+        );
+        en.insert(
+            "diff_truncated".to_string(),
+            "{0} {1} truncated: kept first {2} and last {3} of {4} bytes".to_string(),
+        );
+        en.insert(
+            "diff_stdin_same".to_string(),
+            "stdin: identical ({0})".to_string(),
+        );
+        en.insert(
+            "diff_stdin_changed".to_string(),
+            "stdin: changed ({0} -> {1})".to_string(),
+        );
+        en.insert(
+            "env_changed".to_string(),
+            "environment changed:".to_string(),
+        );
+        en.insert("stdin_not_recorded".to_string(), "not recorded".to_string());
+        en.insert("diff_signal".to_string(), "signal: {0} -> {1}".to_string());
+        en.insert("signal_none".to_string(), "none".to_string());
         en.insert(
             "diff_execution_time".to_string(),
             "execution time: {0}ms -> {1}ms".to_string(),
         );
+        en.insert(
+            "diff_cpu_time".to_string(),
+            "cpu time: {0} -> {1} ({2})".to_string(),
+        );
+        en.insert(
+            "diff_max_rss".to_string(),
+            "max RSS: {0} -> {1} ({2})".to_string(),
+        );
+        en.insert(
+            "diff_ctx_switches".to_string(),
+            "context switches: {0} -> {1} ({2})".to_string(),
+        );
         en.insert("stdout_diff".to_string(), "stdout diff:".to_string());
         en.insert(
             "preview_stdout_header".to_string(),
             "Preview: stdout".to_string(),
         );
+        en.insert(
+            "preview_combined_header".to_string(),
+            "Preview: combined stdout+stderr".to_string(),
+        );
+        en.insert(
+            "preview_no_timeline".to_string(),
+            "(no timeline recorded for this run; press o for stdout/stderr)".to_string(),
+        );
         en.insert(
             "preview_stderr_header".to_string(),
             "Preview: stderr".to_string(),
@@ -411,6 +776,12 @@ impl I18n {
             "o/←/→ to switch".to_string(),
         );
         en.insert("preview_path_label".to_string(), "Path: {0}".to_string());
+        en.insert("preview_git_label".to_string(), "Git: {0}".to_string());
+        en.insert("preview_host_label".to_string(), "Host: {0}".to_string());
+        en.insert(
+            "preview_tracked_files".to_string(),
+            "Tracked files:".to_string(),
+        );
         en.insert(
             "preview_path_missing".to_string(),
             "Path: unavailable".to_string(),
@@ -461,6 +832,14 @@ impl I18n {
             "preview_help_start_diff".to_string(),
             "Toggle selection / start diff (2 selected): Enter".to_string(),
         );
+        en.insert(
+            "preview_help_combined".to_string(),
+            "Toggle stdout|stderr / combined timeline: o".to_string(),
+        );
+        en.insert(
+            "preview_help_rerun".to_string(),
+            "Re-run and diff: r".to_string(),
+        );
         en.insert(
             "preview_help_toggle".to_string(),
             "Toggle help: ?".to_string(),
@@ -504,6 +883,10 @@ impl I18n {
             "selection_help_delete".to_string(),
             "Delete: Ctrl+X or Shift+Backspace (press again to confirm)".to_string(),
         );
+        en.insert(
+            "selection_help_rerun".to_string(),
+            "Re-run: Ctrl+R re-executes the highlighted run and diffs against it".to_string(),
+        );
         en.insert("stderr_diff".to_string(), "stderr diff:".to_string());
         en.insert(
             "output_identical".to_string(),
@@ -561,6 +944,34 @@ impl I18n {
             "error_save_stderr".to_string(),
             "Failed to save stderr".to_string(),
         );
+        en.insert(
+            "error_save_stdin".to_string(),
+            "Failed to save stdin".to_string(),
+        );
+        en.insert(
+            "error_save_timeline".to_string(),
+            "Failed to save output timeline".to_string(),
+        );
+        en.insert(
+            "error_save_artifacts".to_string(),
+            "Failed to save tracked files".to_string(),
+        );
+        en.insert(
+            "error_snapshot_artifacts".to_string(),
+            "Failed to snapshot tracked files".to_string(),
+        );
+        en.insert(
+            "error_read_tracked_file".to_string(),
+            "Failed to read tracked file {0}".to_string(),
+        );
+        en.insert(
+            "track_bad_glob".to_string(),
+            "Invalid --track pattern '{0}': {1}".to_string(),
+        );
+        en.insert(
+            "error_stdin_not_recorded".to_string(),
+            "No recorded stdin found for '{0}' (run with --record-stdin first)".to_string(),
+        );
         en.insert(
             "error_read_stdout".to_string(),
             "Cannot read stdout".to_string(),
@@ -573,6 +984,18 @@ impl I18n {
             "error_update_index".to_string(),
             "Failed to update index".to_string(),
         );
+        en.insert(
+            "warn_index_rebuilt".to_string(),
+            "Index file was unreadable; rebuilt it from the saved records".to_string(),
+        );
+        en.insert(
+            "error_invalid_shell".to_string(),
+            "Invalid shell '{0}': unbalanced quotes".to_string(),
+        );
+        en.insert(
+            "warn_skipped_record".to_string(),
+            "Warning: skipped unreadable record {0}: {1}".to_string(),
+        );
         en.insert(
             "error_save_archive".to_string(),
             "Failed to save {0} year archive".to_string(),
@@ -585,6 +1008,14 @@ impl I18n {
             "error_execute_command".to_string(),
             "Failed to execute command".to_string(),
         );
+        en.insert(
+            "error_open_pty".to_string(),
+            "Failed to open pseudo-terminal".to_string(),
+        );
+        en.insert(
+            "error_pty_unsupported".to_string(),
+            "PTY capture is not supported on this platform".to_string(),
+        );
 
         // Clean operation
         en.insert(
@@ -618,6 +1049,200 @@ impl I18n {
             "help_show".to_string(),
             "交互选择一条记录并展示 stdout/stderr".to_string(),
         );
+        zh.insert(
+            "help_replay".to_string(),
+            "按原始时间节奏回放一次运行的输出".to_string(),
+        );
+        zh.insert(
+            "help_replay_code".to_string(),
+            "回放指定短码的运行(需提供 COMMAND)".to_string(),
+        );
+        zh.insert(
+            "help_replay_speed".to_string(),
+            "回放速度,如 2x 或 0.5x(默认: 1x)".to_string(),
+        );
+        zh.insert(
+            "replay_no_timeline".to_string(),
+            "此运行未记录时间信息,将直接输出全部内容".to_string(),
+        );
+        zh.insert(
+            "help_rerun".to_string(),
+            "在原工作目录重新执行一次记录并与之比较".to_string(),
+        );
+        zh.insert(
+            "help_rerun_target".to_string(),
+            "记录的短码或记录 ID；省略则交互选择".to_string(),
+        );
+        zh.insert(
+            "help_run_batch".to_string(),
+            "批量运行文件中的命令并汇总哪些发生了变化".to_string(),
+        );
+        zh.insert(
+            "help_run_batch_file".to_string(),
+            "命令文件：每行一条（# 为注释），或包含 [[commands]]（command/name）列表的 .toml 文件"
+                .to_string(),
+        );
+        zh.insert(
+            "help_run_batch_jobs".to_string(),
+            "同时运行的命令数（默认: run.batch_jobs）".to_string(),
+        );
+        zh.insert(
+            "help_run_batch_timeout".to_string(),
+            "每条命令的超时时间，例如 30s".to_string(),
+        );
+        zh.insert(
+            "batch_header".to_string(),
+            "运行 {0} 条命令，并发 {1}".to_string(),
+        );
+        zh.insert(
+            "batch_progress".to_string(),
+            "{0}: 退出码 {1}，耗时 {2}ms（{3}）".to_string(),
+        );
+        zh.insert(
+            "batch_failed_to_run".to_string(),
+            "{0}: 无法运行: {1}".to_string(),
+        );
+        zh.insert("batch_change_new".to_string(), "新命令".to_string());
+        zh.insert("batch_change_unchanged".to_string(), "无变化".to_string());
+        zh.insert("batch_change_output".to_string(), "输出变化".to_string());
+        zh.insert(
+            "batch_change_exit".to_string(),
+            "退出码 {0} → {1}".to_string(),
+        );
+        zh.insert("batch_change_error".to_string(), "未运行".to_string());
+        zh.insert("batch_col_command".to_string(), "命令".to_string());
+        zh.insert("batch_col_exit".to_string(), "退出码".to_string());
+        zh.insert("batch_col_change".to_string(), "变化".to_string());
+        zh.insert("batch_col_code".to_string(), "短码".to_string());
+        zh.insert("batch_col_time".to_string(), "耗时".to_string());
+        zh.insert(
+            "batch_totals".to_string(),
+            "共 {0} 条命令：{1} 条变化，{2} 条新增，{3} 条无变化，{4} 条失败".to_string(),
+        );
+        zh.insert(
+            "batch_file_read".to_string(),
+            "读取批处理文件 {0} 失败: {1}".to_string(),
+        );
+        zh.insert(
+            "batch_bad_toml".to_string(),
+            "批处理文件 {0} 无效: {1}".to_string(),
+        );
+        zh.insert("batch_empty".to_string(), "{0} 中没有命令".to_string());
+        zh.insert(
+            "rerun_header".to_string(),
+            "重新执行 `{0}`（短码 {1}），目录 {2}".to_string(),
+        );
+        zh.insert(
+            "rerun_target_not_found".to_string(),
+            "找不到短码或记录 ID 为 '{0}' 的记录".to_string(),
+        );
+        zh.insert(
+            "rerun_missing_dir".to_string(),
+            "记录的工作目录 {0} 已不存在".to_string(),
+        );
+        zh.insert(
+            "help_watch".to_string(),
+            "周期性重新执行命令并高亮变化的行".to_string(),
+        );
+        zh.insert(
+            "help_watch_interval".to_string(),
+            "两次执行之间的间隔，如 5s 或 500ms（默认：2s）".to_string(),
+        );
+        zh.insert(
+            "help_watch_changes_only".to_string(),
+            "仅保存输出与上次保存结果不同的执行".to_string(),
+        );
+        zh.insert(
+            "help_watch_count".to_string(),
+            "执行 N 次后停止".to_string(),
+        );
+        zh.insert("watch_header".to_string(), "每 {0}：{1}".to_string());
+        zh.insert(
+            "watch_unchanged_not_saved".to_string(),
+            "（无变化，未保存）".to_string(),
+        );
+        zh.insert("watch_waiting".to_string(), "等待首次执行...".to_string());
+        zh.insert(
+            "watch_footer".to_string(),
+            "执行：{0}  已保存：{1}  |  q 退出  ↑/↓ PgUp/PgDn 滚动".to_string(),
+        );
+        zh.insert("watch_finished".to_string(), "（已结束）".to_string());
+        zh.insert(
+            "on_change_watching".to_string(),
+            "正在监视 {0} 的变化（Ctrl+C 停止）...".to_string(),
+        );
+        zh.insert(
+            "on_change_triggered".to_string(),
+            "已变化：{0} — 重新执行".to_string(),
+        );
+        zh.insert(
+            "on_change_triggered_many".to_string(),
+            "已变化：{0} 等 {1} 个文件 — 重新执行".to_string(),
+        );
+        zh.insert(
+            "on_change_path_missing".to_string(),
+            "--on-change：'{0}' 不存在".to_string(),
+        );
+        zh.insert(
+            "on_change_bad_glob".to_string(),
+            "--on-change：无效的通配符 '{0}'：{1}".to_string(),
+        );
+        zh.insert(
+            "help_run_on_change".to_string(),
+            "当这些路径/通配符变化时重新执行（逗号分隔或重复指定）".to_string(),
+        );
+        zh.insert(
+            "help_run_debounce".to_string(),
+            "最后一次变化后等待多久再重新执行（默认：300ms）".to_string(),
+        );
+        zh.insert(
+            "help_run_repeat".to_string(),
+            "执行 N 次并汇总不同的输出变体（检测不稳定性）".to_string(),
+        );
+        zh.insert(
+            "help_run_quiet".to_string(),
+            "静默记录：不输出实时内容、提示、摘要或差异".to_string(),
+        );
+        zh.insert(
+            "help_run_no_stream".to_string(),
+            "不实时回显输出，命令结束后再打印".to_string(),
+        );
+        zh.insert(
+            "help_run_json".to_string(),
+            "输出一个 JSON 摘要（record_id、short_code、exit_code、耗时、输出哈希）".to_string(),
+        );
+        zh.insert(
+            "help_run_exit_zero".to_string(),
+            "记录完成后总是以 0 退出，而不是传递命令的退出码".to_string(),
+        );
+        zh.insert(
+            "help_run_diff_exit_code".to_string(),
+            "配合 -d、--repeat 或 --on-change：输出不同时以 STATUS 退出（默认 3）".to_string(),
+        );
+        zh.insert(
+            "help_run_track".to_string(),
+            "运行后快照这些文件、目录或通配符(逗号分隔,相对工作目录),并一起比较".to_string(),
+        );
+        zh.insert(
+            "repeat_run_line".to_string(),
+            "第 {0}/{1} 次：exit={2} {3}ms [短码:{4}]".to_string(),
+        );
+        zh.insert(
+            "repeat_summary".to_string(),
+            "共 {0} 次执行，{1} 种不同的输出变体".to_string(),
+        );
+        zh.insert(
+            "repeat_deterministic".to_string(),
+            "所有执行的输出与退出码完全一致：结果确定".to_string(),
+        );
+        zh.insert(
+            "repeat_variant_line".to_string(),
+            "变体 {0}：{1} 次，exit={2}，短码：{3}".to_string(),
+        );
+        zh.insert(
+            "repeat_variant_diff".to_string(),
+            "━━━ 变体 {0} 与最常见变体对比 ━━━".to_string(),
+        );
         zh.insert(
             "help_show_command".to_string(),
             "要展示的命令及其参数（包含管道/重定向时需整体加引号）".to_string(),
@@ -667,6 +1292,49 @@ impl I18n {
             "help_run_diff_with".to_string(),
             "运行后自动对比：'first'（最早）、'last'（最新）或指定短码".to_string(),
         );
+        zh.insert(
+            "help_run_pty".to_string(),
+            "在伪终端中运行，使工具认为连接了终端（记录原始终端输出）".to_string(),
+        );
+        zh.insert(
+            "help_run_timeout".to_string(),
+            "在 DURATION 后停止命令(如 30s、5m);先发送 TERM,宽限期后发送 KILL".to_string(),
+        );
+        zh.insert(
+            "help_run_record_stdin".to_string(),
+            "随记录保存一份标准输入".to_string(),
+        );
+        zh.insert(
+            "help_run_stdin_from".to_string(),
+            "使用先前运行记录的标准输入('last' 或短码)".to_string(),
+        );
+        zh.insert(
+            "help_run_shell".to_string(),
+            "运行命令所用的 shell,如 \"bash -o pipefail\"(默认: run.shell)".to_string(),
+        );
+        zh.insert(
+            "help_config_run_pty".to_string(),
+            "run.pty: true | false（默认通过伪终端捕获输出；默认：false）".to_string(),
+        );
+        zh.insert(
+            "help_config_run_kill_grace".to_string(),
+            "run.kill_grace_secs: 超时后 SIGTERM 与 SIGKILL 之间的等待秒数(默认: 5)".to_string(),
+        );
+        zh.insert("help_config_run_env".to_string(), "run.env_allowlist / run.env_denylist: 每次运行记录的环境变量(支持 `*` 通配;黑名单优先)".to_string());
+        zh.insert(
+            "help_config_run_shell".to_string(),
+            "run.shell: dt run 使用的 shell 及参数(默认: \"sh\")".to_string(),
+        );
+        zh.insert(
+            "help_config_run_batch_jobs".to_string(),
+            "run.batch_jobs: dt run-batch 同时运行的命令数(默认: 4)".to_string(),
+        );
+        zh.insert(
+            "help_config_run_capture_git".to_string(),
+            "run.capture_git: 在 git 仓库中记录 HEAD 提交、分支及是否有未提交修改(默认: true)"
+                .to_string(),
+        );
+        zh.insert("help_config_storage_compression".to_string(), "storage.compression / compression_threshold: 用 none、gzip 或 zstd 压缩不小于该字节数的 stdout/stderr(默认: none, 65536)".to_string());
         // removed: help_clean_mode (not used)
         zh.insert("short_code_label".to_string(), "短码".to_string());
         zh.insert("time_label".to_string(), "时间".to_string());
@@ -698,6 +1366,22 @@ impl I18n {
             "可选的查询（子串/子序列）".to_string(),
         );
         zh.insert("help_ls_json".to_string(), "输出 JSON".to_string());
+        zh.insert(
+            "help_ls_signal".to_string(),
+            "仅显示被信号终止的运行(名称、编号或 'any')".to_string(),
+        );
+        zh.insert(
+            "help_ls_commit".to_string(),
+            "只显示提交哈希以此前缀开头的记录".to_string(),
+        );
+        zh.insert(
+            "help_ls_branch".to_string(),
+            "只显示在此分支上的记录".to_string(),
+        );
+        zh.insert(
+            "help_ls_host".to_string(),
+            "仅显示在该主机上记录的运行(完整或短主机名)".to_string(),
+        );
         // Dangerous command confirmations (zh)
         zh.insert(
             "confirm_clean_all_title".to_string(),
@@ -752,6 +1436,14 @@ impl I18n {
             "command_completed".to_string(),
             "命令执行完成，退出码: {0}".to_string(),
         );
+        zh.insert(
+            "run_timed_out".to_string(),
+            "命令在 {0} 后超时,已被终止".to_string(),
+        );
+        zh.insert(
+            "run_terminated_by_signal".to_string(),
+            "命令被 {0} 终止".to_string(),
+        );
         zh.insert("execution_time".to_string(), "执行时间".to_string());
         zh.insert("stdout".to_string(), "标准输出:".to_string());
         zh.insert("stderr".to_string(), "错误输出:".to_string());
@@ -837,7 +1529,7 @@ impl I18n {
         );
         zh.insert(
             "status_nav_compact".to_string(),
-            "选择: 输入/jk, PgUp/PgDn或^f/^b翻页, 空格/Enter切换, Ctrl+X 删除(二次确认) Tab入预览 | 预览: jk返回, Shift/Ctrl+↑↓逐行, Enter比较, q返回, Q退出".to_string(),
+            "选择: 输入/jk, PgUp/PgDn或^f/^b翻页, 空格/Enter切换, Ctrl+X 删除(二次确认), Ctrl+R 重新执行, Tab入预览 | 预览: jk返回, Shift/Ctrl+↑↓逐行, Enter比较, q返回, Q退出".to_string(),
         );
         zh.insert(
             "status_nav_compact_show".to_string(),
@@ -863,6 +1555,14 @@ impl I18n {
             "delete_success_status".to_string(),
             "已删除 {0} 的记录。".to_string(),
         );
+        zh.insert(
+            "rerun_success_status".to_string(),
+            "已重新执行并保存，短码 {0}。".to_string(),
+        );
+        zh.insert(
+            "rerun_failed_status".to_string(),
+            "重新执行失败：{0}".to_string(),
+        );
         zh.insert(
             "delete_failed_status".to_string(),
             "删除失败：{0}".to_string(),
@@ -930,15 +1630,99 @@ impl I18n {
             "diff_exit_code".to_string(),
             "退出码: {0} -> {1}".to_string(),
         );
+        zh.insert(
+            "diff_capture_mode".to_string(),
+            "捕获模式: {0} -> {1}".to_string(),
+        );
+        zh.insert("diff_shell".to_string(), "shell: {0} -> {1}".to_string());
+        zh.insert(
+            "diff_git_commit".to_string(),
+            "提交: {0} -> {1}".to_string(),
+        );
+        zh.insert("git_changed".to_string(), "Git 状态已变化:".to_string());
+        zh.insert("host_label".to_string(), "主机".to_string());
+        zh.insert("diff_host".to_string(), "主机: {0} -> {1}".to_string());
+        zh.insert("host_changed".to_string(), "主机已变化:".to_string());
+        zh.insert("tracked_files_diff".to_string(), "跟踪的文件:".to_string());
+        zh.insert(
+            "tracked_files_unchanged".to_string(),
+            "{0} 个跟踪文件未变化".to_string(),
+        );
+        zh.insert("tracked_file_modified".to_string(), "~ {0}".to_string());
+        zh.insert(
+            "tracked_file_added".to_string(),
+            "+ {0} (新增, {1} 字节)".to_string(),
+        );
+        zh.insert(
+            "tracked_file_removed".to_string(),
+            "- {0} (已删除)".to_string(),
+        );
+        zh.insert(
+            "tracked_file_binary".to_string(),
+            "  二进制文件已变化: {0} -> {1} 字节".to_string(),
+        );
+        zh.insert("git_none".to_string(), "不在 git 仓库中".to_string());
+        zh.insert(
+            "diff_termination".to_string(),
+            "结束方式: {0} -> {1}".to_string(),
+        );
+        zh.insert(
+            "output_truncated_marker".to_string(),
+            "[... 省略 {0} 字节（storage.max_output_bytes）...]".to_string(),
+        );
+        zh.insert(
+            "output_view_marker".to_string(),
+            "[... 未显示 {0} 字节；完整输出已保存 ...]".to_string(),
+        );
+        zh.insert(
+            "error_stage_output".to_string(),
+            "创建输出暂存文件失败".to_string(),
+        );
+        zh.insert(
+            "diff_truncated".to_string(),
+            "{0} {1} 已截断：保留前 {2} 与后 {3} 字节（共 {4} 字节）".to_string(),
+        );
+        zh.insert(
+            "diff_stdin_same".to_string(),
+            "标准输入: 相同 ({0})".to_string(),
+        );
+        zh.insert(
+            "diff_stdin_changed".to_string(),
+            "标准输入: 已变化 ({0} -> {1})".to_string(),
+        );
+        zh.insert("env_changed".to_string(), "环境变量已变化:".to_string());
+        zh.insert("stdin_not_recorded".to_string(), "未记录".to_string());
+        zh.insert("diff_signal".to_string(), "信号: {0} -> {1}".to_string());
+        zh.insert("signal_none".to_string(), "无".to_string());
         zh.insert(
             "diff_execution_time".to_string(),
             "执行时间: {0}ms -> {1}ms".to_string(),
         );
+        zh.insert(
+            "diff_cpu_time".to_string(),
+            "CPU 时间: {0} -> {1} ({2})".to_string(),
+        );
+        zh.insert(
+            "diff_max_rss".to_string(),
+            "峰值内存 (RSS): {0} -> {1} ({2})".to_string(),
+        );
+        zh.insert(
+            "diff_ctx_switches".to_string(),
+            "上下文切换: {0} -> {1} ({2})".to_string(),
+        );
         zh.insert("stdout_diff".to_string(), "标准输出差异:".to_string());
         zh.insert(
             "preview_stdout_header".to_string(),
             "输出预览（stdout）".to_string(),
         );
+        zh.insert(
+            "preview_combined_header".to_string(),
+            "预览: 合并的 stdout+stderr".to_string(),
+        );
+        zh.insert(
+            "preview_no_timeline".to_string(),
+            "(此运行未记录时间线;按 o 查看 stdout/stderr)".to_string(),
+        );
         zh.insert(
             "preview_stderr_header".to_string(),
             "输出预览（stderr）".to_string(),
@@ -957,6 +1741,12 @@ impl I18n {
         );
         zh.insert("preview_toggle_short".to_string(), "o/←/→ 切换".to_string());
         zh.insert("preview_path_label".to_string(), "路径: {0}".to_string());
+        zh.insert("preview_git_label".to_string(), "Git: {0}".to_string());
+        zh.insert("preview_host_label".to_string(), "主机: {0}".to_string());
+        zh.insert(
+            "preview_tracked_files".to_string(),
+            "跟踪的文件:".to_string(),
+        );
         zh.insert(
             "preview_path_missing".to_string(),
             "路径: 暂不可用".to_string(),
@@ -1007,6 +1797,14 @@ impl I18n {
             "preview_help_start_diff".to_string(),
             "切换/对比（已选2条时）: Enter".to_string(),
         );
+        zh.insert(
+            "preview_help_combined".to_string(),
+            "切换 stdout|stderr / 合并时间线: o".to_string(),
+        );
+        zh.insert(
+            "preview_help_rerun".to_string(),
+            "重新执行并比较: r".to_string(),
+        );
         zh.insert("preview_help_toggle".to_string(), "切换帮助: ?".to_string());
         zh.insert("preview_help_quit".to_string(), "退出程序: Q".to_string());
 
@@ -1044,6 +1842,10 @@ impl I18n {
             "selection_help_delete".to_string(),
             "删除记录: Ctrl+X 或 Shift+Backspace（再次触发以确认删除）".to_string(),
         );
+        zh.insert(
+            "selection_help_rerun".to_string(),
+            "重新执行: Ctrl+R 重新执行当前记录并与之比较".to_string(),
+        );
         zh.insert("stderr_diff".to_string(), "错误输出差异:".to_string());
         zh.insert("output_identical".to_string(), "输出完全一致".to_string());
         zh.insert(
@@ -1106,6 +1908,34 @@ impl I18n {
             "error_save_stderr".to_string(),
             "保存错误输出失败".to_string(),
         );
+        zh.insert(
+            "error_save_stdin".to_string(),
+            "保存标准输入失败".to_string(),
+        );
+        zh.insert(
+            "error_save_timeline".to_string(),
+            "保存输出时间线失败".to_string(),
+        );
+        zh.insert(
+            "error_save_artifacts".to_string(),
+            "保存跟踪文件失败".to_string(),
+        );
+        zh.insert(
+            "error_snapshot_artifacts".to_string(),
+            "无法快照跟踪文件".to_string(),
+        );
+        zh.insert(
+            "error_read_tracked_file".to_string(),
+            "无法读取跟踪文件 {0}".to_string(),
+        );
+        zh.insert(
+            "track_bad_glob".to_string(),
+            "无效的 --track 模式 '{0}': {1}".to_string(),
+        );
+        zh.insert(
+            "error_stdin_not_recorded".to_string(),
+            "未找到 '{0}' 的标准输入记录(请先使用 --record-stdin 运行)".to_string(),
+        );
         zh.insert(
             "error_read_stdout".to_string(),
             "无法读取标准输出".to_string(),
@@ -1115,6 +1945,18 @@ impl I18n {
             "无法读取错误输出".to_string(),
         );
         zh.insert("error_update_index".to_string(), "更新索引失败".to_string());
+        zh.insert(
+            "warn_index_rebuilt".to_string(),
+            "索引文件无法读取，已根据已保存的记录重建".to_string(),
+        );
+        zh.insert(
+            "error_invalid_shell".to_string(),
+            "无效的 shell“{0}”：引号不匹配".to_string(),
+        );
+        zh.insert(
+            "warn_skipped_record".to_string(),
+            "警告：已跳过无法读取的记录 {0}：{1}".to_string(),
+        );
         zh.insert(
             "error_save_archive".to_string(),
             "保存 {0} 年归档失败".to_string(),
@@ -1127,6 +1969,11 @@ impl I18n {
             "error_execute_command".to_string(),
             "执行命令失败".to_string(),
         );
+        zh.insert("error_open_pty".to_string(), "打开伪终端失败".to_string());
+        zh.insert(
+            "error_pty_unsupported".to_string(),
+            "当前平台不支持伪终端捕获".to_string(),
+        );
 
         // Clean operation
         zh.insert(
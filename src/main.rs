@@ -1,26 +1,37 @@
+mod artifacts;
 mod bash_parser;
+mod batch;
+mod compression;
 mod config;
 mod differ;
+mod encoding;
 mod executor;
 mod fuzzy_matcher;
+mod git;
+mod host;
 mod i18n;
+mod on_change;
+mod replay;
+mod rerun;
 mod storage;
 mod store_manager;
+mod variants;
+mod watch;
 
 use anyhow::Result;
 use clap::{Args, Parser, Subcommand};
 use colored::*;
 use std::fmt;
 use std::fs;
-use std::io::{self, Read, Write};
+use std::io::{self, IsTerminal, Read, Write};
 use std::path::PathBuf;
 use std::str::FromStr;
 
 use config::Config;
 use differ::Differ;
-use executor::CommandExecutor;
+use executor::{CommandExecutor, ExecOptions, StdinSource};
 use i18n::I18n;
-use storage::CommandExecution;
+use storage::{CommandExecution, TerminationReason};
 use store_manager::StoreManager;
 
 /// Represents the target execution to compare against after running a command.
@@ -69,6 +80,9 @@ struct Cli {
     /// Override data directory (default: ~/.dt)
     #[arg(long = "data-dir", global = true)]
     data_dir: Option<PathBuf>,
+    /// Decode output with this encoding instead of detecting it (e.g. latin1, utf-16le, gbk)
+    #[arg(long = "encoding", global = true, value_parser = encoding::parse_label)]
+    encoding: Option<&'static encoding_rs::Encoding>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -77,6 +91,18 @@ struct Cli {
 enum Commands {
     /// Execute command and record output
     Run(RunArgs),
+    /// Run every command in a file and summarize which ones changed
+    #[command(name = "run-batch")]
+    RunBatch {
+        /// Commands, one per line, or a TOML file with a [[commands]] list
+        file: PathBuf,
+        /// How many commands run at once [default: run.batch_jobs]
+        #[arg(short = 'j', long = "jobs", value_parser = clap::value_parser!(u32).range(1..))]
+        jobs: Option<u32>,
+        /// Stop each command after this long (e.g. 30s)
+        #[arg(long = "timeout", value_parser = executor::parse_duration)]
+        timeout: Option<std::time::Duration>,
+    },
     /// Compare command output differences
     Diff {
         /// Command to compare (wrap commands with pipes in quotes)
@@ -95,6 +121,38 @@ enum Commands {
         #[arg(trailing_var_arg = true)]
         command: Vec<String>,
     },
+    /// Play back a recorded run with its original output timing
+    Replay {
+        /// Command whose run to replay; omit to pick one interactively
+        #[arg(trailing_var_arg = true)]
+        command: Vec<String>,
+        /// Replay the run with this short code instead of opening the picker
+        #[arg(long = "code", requires = "command")]
+        code: Option<String>,
+        /// Playback speed, e.g. 2x or 0.5x
+        #[arg(long = "speed", default_value = "1x", value_parser = replay::parse_speed)]
+        speed: f64,
+    },
+    /// Re-execute a recorded run in its original directory and diff against it
+    Rerun {
+        /// Short code or record id of the run; omit to pick one interactively
+        target: Option<String>,
+    },
+    /// Re-run a command periodically and highlight changed lines
+    Watch {
+        /// Command to watch (wrap commands with pipes in quotes)
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+        /// Time between runs, e.g. 5s or 500ms
+        #[arg(short = 'n', long = "interval", default_value = "2s", value_parser = executor::parse_duration)]
+        interval: std::time::Duration,
+        /// Only save runs whose output differs from the last saved run
+        #[arg(long = "changes-only")]
+        changes_only: bool,
+        /// Stop after this many runs
+        #[arg(long = "count")]
+        count: Option<usize>,
+    },
     /// Clean history records
     Clean {
         /// Clean mode
@@ -108,6 +166,18 @@ enum Commands {
         /// Output JSON instead of text
         #[arg(long = "json")]
         json: bool,
+        /// Only show runs killed by a signal: a name (SIGSEGV, segv), a number, or 'any'
+        #[arg(long = "signal")]
+        signal: Option<String>,
+        /// Only show runs recorded at a commit starting with this hash prefix
+        #[arg(long = "commit")]
+        commit: Option<String>,
+        /// Only show runs recorded on this git branch
+        #[arg(long = "branch")]
+        branch: Option<String>,
+        /// Only show runs recorded on this host (full or short hostname)
+        #[arg(long = "host")]
+        host: Option<String>,
     },
     /// Parse a Bash snippet/file to AST (tree-sitter-bash)
     Parse {
@@ -146,6 +216,11 @@ enum CleanMode {
 
 /// Arguments for the Run command.
 #[derive(Args)]
+#[command(group(
+    clap::ArgGroup::new("compared")
+        .args(["diff_with", "repeat", "on_change"])
+        .multiple(true)
+))]
 struct RunArgs {
     /// Command to execute (wrap piped expressions in quotes)
     #[arg(required = true, trailing_var_arg = true)]
@@ -155,6 +230,69 @@ struct RunArgs {
     /// Examples: -d first, -d last, -d a, -d ab
     #[arg(long = "diff-with", short = 'd')]
     diff_with: Option<String>,
+
+    /// Run the command under a pseudo-terminal so tools see a TTY
+    #[arg(long = "pty")]
+    pty: bool,
+
+    /// Stop the command after this long (e.g. 500ms, 30s, 5m); SIGTERM first, then SIGKILL
+    #[arg(long = "timeout", value_parser = executor::parse_duration)]
+    timeout: Option<std::time::Duration>,
+
+    /// Save a copy of stdin with the record so the run can be reproduced
+    #[arg(long = "record-stdin")]
+    record_stdin: bool,
+
+    /// Feed stdin recorded by an earlier run of this command ('last' or a short code)
+    #[arg(long = "stdin-from", conflicts_with = "record_stdin")]
+    stdin_from: Option<String>,
+
+    /// Shell to run the command with, including arguments (e.g. "bash -o pipefail")
+    #[arg(long = "shell")]
+    shell: Option<String>,
+
+    /// Run the command N times and summarize the distinct output variants
+    #[arg(long = "repeat", value_name = "N", conflicts_with_all = ["on_change", "diff_with"], value_parser = clap::value_parser!(u32).range(1..))]
+    repeat: Option<u32>,
+
+    /// Re-run whenever these paths or globs change (comma-separated or repeated)
+    #[arg(long = "on-change", value_delimiter = ',')]
+    on_change: Vec<String>,
+
+    /// Snapshot these files, directories or globs after the run so diffs cover them too
+    #[arg(long = "track", value_name = "PATHS", value_delimiter = ',')]
+    track: Vec<String>,
+
+    /// Exit 0 once the run is recorded instead of passing on the command's exit status
+    #[arg(long = "exit-zero")]
+    exit_zero: bool,
+
+    /// With -d, --repeat or --on-change: exit with this status (default 3) when the output differs
+    #[arg(
+        long = "diff-exit-code",
+        value_name = "STATUS",
+        requires = "compared",
+        require_equals = true,
+        num_args = 0..=1,
+        default_missing_value = "3"
+    )]
+    diff_exit_code: Option<i32>,
+
+    /// Record silently: no live output, banners, summary or diff
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+
+    /// Don't echo output live; print it once the command has finished
+    #[arg(long = "no-stream")]
+    no_stream: bool,
+
+    /// Print a single JSON summary of the run instead of banners and output
+    #[arg(long = "json", conflicts_with_all = ["quiet", "diff_with", "on_change"])]
+    json: bool,
+
+    /// Quiet period after the last file change before re-running
+    #[arg(long = "debounce", default_value = "300ms", value_parser = executor::parse_duration)]
+    debounce: std::time::Duration,
 }
 
 fn main() -> Result<()> {
@@ -207,12 +345,39 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
     let config = Config::new()?;
     let i18n = I18n::new(&config.get_effective_language());
-    let store =
+    let mut store =
         StoreManager::new_with_config_and_base_dir(config.clone(), &i18n, cli.data_dir.clone())?;
+    store.set_encoding_override(cli.encoding);
 
     match cli.command {
         Commands::Run(run_args) => {
-            let RunArgs { command, diff_with } = run_args;
+            let RunArgs {
+                command,
+                diff_with,
+                pty,
+                timeout,
+                record_stdin,
+                stdin_from,
+                shell,
+                repeat,
+                on_change,
+                track,
+                debounce,
+                quiet,
+                no_stream,
+                json,
+                exit_zero,
+                diff_exit_code,
+            } = run_args;
+            let report = if json {
+                RunReport::Json
+            } else if quiet {
+                RunReport::Quiet
+            } else if no_stream {
+                RunReport::AfterCompletion
+            } else {
+                RunReport::Live
+            };
 
             let command_str = join_args_for_shell(&command);
             let command_hash = storage::hash_command(&command_str);
@@ -235,42 +400,57 @@ fn main() -> Result<()> {
                 false
             };
 
-            let mut execution = CommandExecutor::execute(&command_str, &i18n)?;
-            // Assign minimal unused short code for this command
-            store.assign_short_code(&mut execution.record, &i18n)?;
-
-            println!(
-                "{}",
-                i18n.t_format(
-                    "command_completed",
-                    &[&execution.record.exit_code.to_string()]
-                )
-                .green()
-                .bold()
-            );
-            println!(
-                "{}: {}ms",
-                i18n.t("execution_time").yellow(),
-                execution.record.duration_ms.to_string().green()
-            );
+            let stdin = if let Some(selector) = stdin_from {
+                StdinSource::Replay(load_recorded_stdin(
+                    &store,
+                    &command_hash,
+                    &selector,
+                    &i18n,
+                )?)
+            } else if record_stdin {
+                StdinSource::Record
+            } else {
+                StdinSource::Inherit
+            };
 
-            if !execution.stdout.is_empty() && !execution.streamed_stdout {
-                println!("{}", i18n.t("stdout").cyan().bold());
-                println!("{}", execution.stdout);
+            let mut exec_options = exec_options_from_config(&config, &store, cli.encoding, &i18n)?;
+            exec_options.pty |= pty;
+            exec_options.timeout = timeout;
+            exec_options.stdin = stdin;
+            exec_options.track = track;
+            if let Some(shell) = shell.as_deref() {
+                exec_options.shell = executor::parse_shell(shell, &i18n)?;
             }
-
-            if !execution.stderr.is_empty() && !execution.streamed_stderr {
-                println!("{}", i18n.t("stderr").red().bold());
-                println!("{}", execution.stderr.red());
+            exec_options.quiet = report != RunReport::Live;
+            if let Some(times) = repeat {
+                let executions =
+                    run_repeated(&command_str, times, exec_options, report, &store, &i18n)?;
+                let output_differs = variants::group_variants(&executions).len() > 1;
+                let command_status = executions
+                    .iter()
+                    .map(|e| exit_status_of(&e.record))
+                    .find(|&status| status != 0)
+                    .unwrap_or(0);
+                return exit_with_run_status(
+                    command_status,
+                    output_differs,
+                    exit_zero,
+                    diff_exit_code,
+                );
             }
 
-            store.save_execution(&execution, &i18n)?;
-            println!("{}", i18n.t("result_saved").green().bold());
-            if let Some(code) = &execution.record.short_code {
-                println!("{}", i18n.t_format("assigned_short_code", &[code]).yellow());
+            let mut execution = CommandExecutor::execute(&command_str, &exec_options, &i18n)?;
+            // Assign minimal unused short code for this command
+            store.assign_short_code(&mut execution.record, &i18n)?;
+            report_and_save_run(&mut execution, timeout, report, &store, &i18n)?;
+            if let (true, Some(code)) = (report.shows_banners(), &execution.record.short_code) {
                 println!("{}", i18n.t_format("hint_diff_with_code", &[code]).dimmed());
             }
 
+            let mut output_differs = false;
+            // `--quiet` hides the diff too; `--diff-exit-code` still reports it.
+            let shows_diff = report.shows_banners();
+
             // If a diff target code is provided, show diff immediately
             if let Some(code) = diff_code {
                 let executions = store.find_executions(&command_hash, &i18n)?;
@@ -280,9 +460,12 @@ fn main() -> Result<()> {
                     .filter(|e| e.record.record_id != execution.record.record_id)
                     .find(|e| e.record.short_code.as_deref() == Some(code.as_str()))
                 {
+                    output_differs = Differ::outputs_differ(&target, &execution);
                     let mut pair = vec![target, execution.clone()];
-                    pair.sort_by(|a, b| a.record.timestamp.cmp(&b.record.timestamp));
-                    if let Some(diff_output) = Differ::diff_executions(&pair, &i18n, false) {
+                    pair.sort_by_key(|e| e.record.timestamp);
+                    if let (true, Some(diff_output)) =
+                        (shows_diff, Differ::diff_executions(&pair, &i18n, false))
+                    {
                         print!("{}", diff_output);
                     }
                 } else {
@@ -293,22 +476,139 @@ fn main() -> Result<()> {
             // Auto-diff with first/last if requested
             if let Some(target_flag) = diff_target {
                 if !had_previous_runs {
-                    println!(
-                        "{}",
-                        "No previous executions found for this command".yellow()
-                    );
+                    if shows_diff {
+                        println!(
+                            "{}",
+                            "No previous executions found for this command".yellow()
+                        );
+                    }
                 } else {
-                    let exclude_timestamp =
-                        u64::try_from(execution.record.timestamp.timestamp()).unwrap_or_default();
-                    match store.get_target_record(&command_hash, target_flag, exclude_timestamp)? {
+                    match store.get_target_record(
+                        &command_hash,
+                        target_flag,
+                        &execution.record.record_id,
+                    )? {
                         Some(target_execution) => {
-                            Differ::auto_diff(&execution, &target_execution, target_flag)?;
+                            output_differs = Differ::outputs_differ(&target_execution, &execution);
+                            if shows_diff {
+                                Differ::auto_diff(
+                                    &execution,
+                                    &target_execution,
+                                    target_flag,
+                                    &i18n,
+                                )?;
+                            }
                         }
-                        None => {
+                        None if shows_diff => {
                             println!("{}", "Only current execution exists".yellow());
                         }
+                        None => {}
+                    }
+                }
+            }
+
+            if !on_change.is_empty() {
+                let watcher = on_change::ChangeWatcher::new(
+                    &on_change,
+                    &[store.base_dir().to_path_buf()],
+                    &i18n,
+                )?;
+                // Stdin can only be read once; later runs get the bytes captured by the first.
+                if let Some(recorded) = execution.stdin_raw.clone() {
+                    exec_options.stdin = StdinSource::Replay(recorded);
+                }
+                // Like `--repeat`, the first failed run decides the exit status.
+                let mut command_status = exit_status_of(&execution.record);
+                let mut previous = execution;
+                loop {
+                    if report.shows_banners() {
+                        println!();
+                        println!(
+                            "{}",
+                            i18n.t_format("on_change_watching", &[&on_change.join(", ")])
+                                .dimmed()
+                        );
                     }
+                    let Some(changed) = watcher.wait_for_change(debounce) else {
+                        break;
+                    };
+                    if report.shows_banners() {
+                        let first = changed
+                            .first()
+                            .map(|p| {
+                                std::env::current_dir()
+                                    .ok()
+                                    .and_then(|cwd| pathdiff::diff_paths(p, cwd))
+                                    .unwrap_or_else(|| p.clone())
+                                    .display()
+                                    .to_string()
+                            })
+                            .unwrap_or_default();
+                        let label = if changed.len() > 1 {
+                            i18n.t_format(
+                                "on_change_triggered_many",
+                                &[&first, &(changed.len() - 1).to_string()],
+                            )
+                        } else {
+                            i18n.t_format("on_change_triggered", &[&first])
+                        };
+                        println!("{}", label.cyan().bold());
+                    }
+
+                    let mut next = CommandExecutor::execute(&command_str, &exec_options, &i18n)?;
+                    store.assign_short_code(&mut next.record, &i18n)?;
+                    report_and_save_run(&mut next, timeout, report, &store, &i18n)?;
+                    if report.shows_banners() {
+                        Differ::auto_diff(&next, &previous, DiffTarget::Last, &i18n)?;
+                    }
+                    watcher.run_finished(debounce);
+                    output_differs |= Differ::outputs_differ(&previous, &next);
+                    if command_status == 0 {
+                        command_status = exit_status_of(&next.record);
+                    }
+                    previous = next;
                 }
+                return exit_with_run_status(
+                    command_status,
+                    output_differs,
+                    exit_zero,
+                    diff_exit_code,
+                );
+            }
+
+            exit_with_run_status(
+                exit_status_of(&execution.record),
+                output_differs,
+                exit_zero,
+                diff_exit_code,
+            )?;
+        }
+        Commands::RunBatch {
+            file,
+            jobs,
+            timeout,
+        } => {
+            let entries = batch::parse_batch_file(&file, &i18n)?;
+            let jobs = jobs
+                .map_or(config.run.batch_jobs, |j| j as usize)
+                .clamp(1, entries.len());
+            let mut exec_options = exec_options_from_config(&config, &store, cli.encoding, &i18n)?;
+            exec_options.timeout = timeout;
+            println!(
+                "{}",
+                i18n.t_format(
+                    "batch_header",
+                    &[&entries.len().to_string(), &jobs.to_string()]
+                )
+                .cyan()
+                .bold()
+            );
+            let results = batch::run_batch(&entries, &exec_options, jobs, &store, &i18n);
+            println!();
+            print!("{}", batch::summary_table(&entries, &results, &i18n));
+            if batch::any_failed(&results) {
+                io::stdout().flush()?;
+                std::process::exit(1);
             }
         }
         Commands::Diff {
@@ -331,6 +631,7 @@ fn main() -> Result<()> {
                     !(v == "0" || v == "false")
                 })
                 .unwrap_or(config.display.alt_screen);
+            let rerun_options = exec_options_from_config(&config, &store, cli.encoding, &i18n)?;
 
             if !command.is_empty() {
                 let command_str = join_args_for_shell(&command);
@@ -356,6 +657,7 @@ fn main() -> Result<()> {
                                 .unwrap_or_default()
                         },
                         Some(|exec: &CommandExecution| store_ref.delete_execution(exec, &i18n)),
+                        Some(rerun::quiet_action(&rerun_options, store_ref, &i18n)),
                     );
                 }
                 if let Some(diff_output) = Differ::diff_executions(&executions, &i18n, linewise) {
@@ -370,25 +672,12 @@ fn main() -> Result<()> {
                     use_alt_screen,
                     max_shown,
                     linewise,
+                    Some(rerun::quiet_action(&rerun_options, &store, &i18n)),
                 )?;
             }
         }
         Commands::Show { command } => {
-            // Resolve TUI settings (env overrides config if present)
-            let tui_simple = std::env::var("DT_TUI")
-                .ok()
-                .map(|v| {
-                    let v = v.to_lowercase();
-                    v == "0" || v == "false" || v == "simple"
-                })
-                .unwrap_or_else(|| config.display.tui_mode.to_lowercase() == "simple");
-            let use_alt_screen = std::env::var("DT_ALT_SCREEN")
-                .ok()
-                .map(|v| {
-                    let v = v.to_lowercase();
-                    !(v == "0" || v == "false")
-                })
-                .unwrap_or(config.display.alt_screen);
+            let (tui_simple, use_alt_screen) = tui_settings(&config);
 
             if !command.is_empty() {
                 let command_str = join_args_for_shell(&command);
@@ -423,8 +712,137 @@ fn main() -> Result<()> {
                 Differ::command_then_show_flow(&store, &i18n, tui_simple, use_alt_screen)?;
             }
         }
-        Commands::Ls { query, json } => {
-            list_records_query(&store, &query.unwrap_or_default(), &i18n, json)?;
+        Commands::Replay {
+            command,
+            code,
+            speed,
+        } => {
+            let (tui_simple, use_alt_screen) = tui_settings(&config);
+            let selected = if command.is_empty() {
+                Differ::command_then_select_flow(&store, &i18n, tui_simple, use_alt_screen)?
+            } else {
+                let command_str = join_args_for_shell(&command);
+                let command_hash = storage::hash_command(&command_str);
+                let executions = store.find_executions(&command_hash, &i18n)?;
+                if executions.is_empty() {
+                    println!("{}", i18n.t("no_records").yellow());
+                    return Ok(());
+                }
+                if let Some(code) = code {
+                    let found = executions
+                        .into_iter()
+                        .find(|e| e.record.short_code.as_deref() == Some(code.as_str()));
+                    if found.is_none() {
+                        println!("{}", i18n.t_format("diff_code_not_found", &[&code]));
+                    }
+                    found
+                } else {
+                    let store_ref = &store;
+                    Differ::select_single_execution(
+                        &executions,
+                        &i18n,
+                        tui_simple,
+                        use_alt_screen,
+                        None,
+                        || {
+                            store_ref
+                                .find_executions(&command_hash, &i18n)
+                                .unwrap_or_default()
+                        },
+                        Some(|exec: &CommandExecution| store_ref.delete_execution(exec, &i18n)),
+                    )
+                }
+            };
+            if let Some(exec) = selected {
+                if exec.timeline.is_none() {
+                    eprintln!("{}", i18n.t("replay_no_timeline").yellow());
+                }
+                replay::replay(&exec, speed)?;
+            }
+        }
+        Commands::Rerun { target } => {
+            let (tui_simple, use_alt_screen) = tui_settings(&config);
+            let original = match target {
+                Some(target) => rerun::resolve_target(&store, &target, &i18n)?,
+                None => match Differ::command_then_select_flow(
+                    &store,
+                    &i18n,
+                    tui_simple,
+                    use_alt_screen,
+                )? {
+                    Some(exec) => exec,
+                    None => return Ok(()),
+                },
+            };
+            let code = original.record.short_code.as_deref().unwrap_or("-");
+            println!(
+                "{}",
+                i18n.t_format(
+                    "rerun_header",
+                    &[
+                        original.record.command_to_run(),
+                        code,
+                        &original.record.working_dir.display().to_string(),
+                    ]
+                )
+                .cyan()
+                .bold()
+            );
+
+            let exec_options = exec_options_from_config(&config, &store, cli.encoding, &i18n)?;
+            let mut execution = rerun::rerun(&original, &exec_options, &store, &i18n)?;
+            report_and_save_run(&mut execution, None, RunReport::Live, &store, &i18n)?;
+            if let Some(diff_output) =
+                Differ::diff_executions(&[original, execution.clone()], &i18n, false)
+            {
+                print!("{}", diff_output);
+            }
+
+            let status = exit_status_of(&execution.record);
+            if status != 0 {
+                io::stdout().flush()?;
+                std::process::exit(status);
+            }
+        }
+        Commands::Watch {
+            command,
+            interval,
+            changes_only,
+            count,
+        } => {
+            let command_str = join_args_for_shell(&command);
+            let (tui_simple, use_alt_screen) = tui_settings(&config);
+            let options = watch::WatchOptions {
+                interval,
+                changes_only,
+                count,
+                interactive: !tui_simple && io::stdout().is_terminal(),
+                use_alt_screen,
+            };
+            watch::Watcher::watch(
+                &command_str,
+                exec_options_from_config(&config, &store, cli.encoding, &i18n)?,
+                &options,
+                &store,
+                &i18n,
+                &config.get_effective_language(),
+            )?;
+        }
+        Commands::Ls {
+            query,
+            json,
+            signal,
+            commit,
+            branch,
+            host,
+        } => {
+            let filter = RecordFilter {
+                signal,
+                commit,
+                branch,
+                host,
+            };
+            list_records_query(&store, &query.unwrap_or_default(), &filter, &i18n, json)?;
         }
         Commands::Parse { file, json } => {
             use bash_parser::{ast_outline, BashParser};
@@ -714,6 +1132,259 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Shell-style exit status for a recorded run: the exit code, or 128+signal
+/// when the command was killed by a signal.
+fn exit_status_of(record: &storage::CommandRecord) -> i32 {
+    match record.signal {
+        Some(signal) => 128 + signal,
+        None if record.exit_code < 0 => 1,
+        None => record.exit_code,
+    }
+}
+
+/// Pass the command's status on so dt can wrap commands in scripts and CI.
+/// A failing command wins over a diff result.
+fn exit_with_run_status(
+    command_status: i32,
+    output_differs: bool,
+    exit_zero: bool,
+    diff_exit_code: Option<i32>,
+) -> Result<()> {
+    let command_status = if exit_zero { 0 } else { command_status };
+    let status = match diff_exit_code {
+        Some(diff_status) if command_status == 0 && output_differs => diff_status,
+        _ => command_status,
+    };
+    if status != 0 {
+        io::stdout().flush()?;
+        std::process::exit(status);
+    }
+    Ok(())
+}
+
+/// How `dt run` reports a run on the terminal.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RunReport {
+    /// Echo output as it is produced, then print banners (default).
+    Live,
+    /// Print output and banners once the command has finished (`--no-stream`).
+    AfterCompletion,
+    /// Print nothing (`--quiet`).
+    Quiet,
+    /// Print one JSON summary object (`--json`).
+    Json,
+}
+
+impl RunReport {
+    fn shows_banners(self) -> bool {
+        matches!(self, Self::Live | Self::AfterCompletion)
+    }
+}
+
+/// Machine-readable summary of a saved run for `dt run --json`.
+fn run_summary_json(execution: &CommandExecution) -> serde_json::Value {
+    let r = &execution.record;
+    serde_json::json!({
+        "record_id": r.record_id,
+        "short_code": r.short_code,
+        "command": r.command,
+        "command_hash": r.command_hash,
+        "timestamp": r.timestamp.to_rfc3339(),
+        "exit_code": r.exit_code,
+        "duration_ms": r.duration_ms,
+        "termination": r.termination,
+        "signal": r.signal,
+        "signal_name": r.signal_name,
+        "stdout_sha256": r.stdout_sha256.clone().unwrap_or_else(|| storage::hash_bytes(execution.stdout_bytes())),
+        "stderr_sha256": r.stderr_sha256.clone().unwrap_or_else(|| storage::hash_bytes(execution.stderr_bytes())),
+        "stdout_bytes": execution.stdout_len(),
+        "stderr_bytes": execution.stderr_len(),
+        "stdout_truncated": r.stdout_truncation.is_some(),
+        "stderr_truncated": r.stderr_truncation.is_some(),
+    })
+}
+
+/// Print the outcome of a finished run (signal/timeout notes, exit code, duration,
+/// output not already streamed) and save it to history.
+fn report_and_save_run(
+    execution: &mut CommandExecution,
+    timeout: Option<std::time::Duration>,
+    report: RunReport,
+    store: &StoreManager,
+    i18n: &I18n,
+) -> Result<()> {
+    if !report.shows_banners() {
+        store.save_execution(execution, i18n)?;
+        if report == RunReport::Json {
+            println!("{}", serde_json::to_string(&run_summary_json(execution))?);
+        }
+        return Ok(());
+    }
+
+    if let Some(signal) = execution.record.signal_label() {
+        println!(
+            "{}",
+            i18n.t_format("run_terminated_by_signal", &[&signal])
+                .red()
+                .bold()
+        );
+    }
+    if execution.record.termination == TerminationReason::TimedOut {
+        let limit = timeout.map(executor::format_duration).unwrap_or_default();
+        println!("{}", i18n.t_format("run_timed_out", &[&limit]).red().bold());
+    }
+
+    println!(
+        "{}",
+        i18n.t_format(
+            "command_completed",
+            &[&execution.record.exit_code.to_string()]
+        )
+        .green()
+        .bold()
+    );
+    println!(
+        "{}: {}ms",
+        i18n.t("execution_time").yellow(),
+        execution.record.duration_ms.to_string().green()
+    );
+
+    if !execution.stdout.is_empty() && !execution.streamed_stdout {
+        println!("{}", i18n.t("stdout").cyan().bold());
+        println!("{}", execution.stdout);
+    }
+
+    if !execution.stderr.is_empty() && !execution.streamed_stderr {
+        println!("{}", i18n.t("stderr").red().bold());
+        println!("{}", execution.stderr.red());
+    }
+
+    store.save_execution(execution, i18n)?;
+    println!("{}", i18n.t("result_saved").green().bold());
+    if let Some(code) = &execution.record.short_code {
+        println!("{}", i18n.t_format("assigned_short_code", &[code]).yellow());
+    }
+    Ok(())
+}
+
+/// `dt run --repeat N`: run the command N times, save every run, then print
+/// the distinct output variants.
+fn run_repeated(
+    command: &str,
+    times: u32,
+    mut exec_options: ExecOptions,
+    report: RunReport,
+    store: &StoreManager,
+    i18n: &I18n,
+) -> Result<Vec<CommandExecution>> {
+    exec_options.quiet = true;
+    let mut executions: Vec<CommandExecution> = Vec::new();
+    for run in 1..=times {
+        let mut execution = CommandExecutor::execute(command, &exec_options, i18n)?;
+        if let Some(recorded) = execution.stdin_raw.clone() {
+            // Stdin can only be read once; later runs get the bytes captured by the first.
+            exec_options.stdin = StdinSource::Replay(recorded);
+        }
+        store.assign_short_code(&mut execution.record, i18n)?;
+        if !report.shows_banners() {
+            // Quiet prints nothing; JSON prints one summary line per run.
+            report_and_save_run(&mut execution, None, report, store, i18n)?;
+            executions.push(execution);
+            continue;
+        }
+        store.save_execution(&mut execution, i18n)?;
+        println!(
+            "{}",
+            i18n.t_format(
+                "repeat_run_line",
+                &[
+                    &run.to_string(),
+                    &times.to_string(),
+                    &execution.record.exit_code.to_string(),
+                    &execution.record.duration_ms.to_string(),
+                    execution.record.short_code.as_deref().unwrap_or("-"),
+                ]
+            )
+        );
+        executions.push(execution);
+    }
+    if report.shows_banners() {
+        println!();
+        print!("{}", variants::summary(&executions, i18n));
+    }
+    Ok(executions)
+}
+
+/// Execution options shared by `run` and `watch`, taken from the `[run]` config section.
+fn exec_options_from_config(
+    config: &Config,
+    store: &StoreManager,
+    encoding: Option<&'static encoding_rs::Encoding>,
+    i18n: &I18n,
+) -> Result<ExecOptions> {
+    Ok(ExecOptions {
+        pty: config.run.pty,
+        timeout: None,
+        kill_grace: std::time::Duration::from_secs(config.run.kill_grace_secs),
+        encoding,
+        stdin: StdinSource::Inherit,
+        env_allowlist: config.run.env_allowlist.clone(),
+        env_denylist: config.run.env_denylist.clone(),
+        shell: executor::parse_shell(&config.run.shell, i18n)?,
+        quiet: false,
+        capture_dir: Some(store.staging_dir()),
+        max_output_bytes: config.storage.max_output_bytes,
+        working_dir: None,
+        capture_git: config.run.capture_git,
+        track: Vec::new(),
+    })
+}
+
+/// Resolve TUI settings (env overrides config if present).
+/// Returns `(tui_simple, use_alt_screen)`.
+fn tui_settings(config: &Config) -> (bool, bool) {
+    let tui_simple = std::env::var("DT_TUI")
+        .ok()
+        .map(|v| {
+            let v = v.to_lowercase();
+            v == "0" || v == "false" || v == "simple"
+        })
+        .unwrap_or_else(|| config.display.tui_mode.to_lowercase() == "simple");
+    let use_alt_screen = std::env::var("DT_ALT_SCREEN")
+        .ok()
+        .map(|v| {
+            let v = v.to_lowercase();
+            !(v == "0" || v == "false")
+        })
+        .unwrap_or(config.display.alt_screen);
+    (tui_simple, use_alt_screen)
+}
+
+/// Load the stdin recorded by an earlier run of the same command.
+/// `selector` is 'last' (most recent run with recorded stdin) or a short code.
+fn load_recorded_stdin(
+    store: &StoreManager,
+    command_hash: &str,
+    selector: &str,
+    i18n: &I18n,
+) -> Result<Vec<u8>> {
+    let executions = store.find_executions(command_hash, i18n)?;
+    let source = if selector.eq_ignore_ascii_case("last") {
+        executions
+            .iter()
+            .filter(|e| e.stdin_path.is_some())
+            .max_by_key(|e| e.record.timestamp)
+    } else {
+        executions
+            .iter()
+            .find(|e| e.record.short_code.as_deref() == Some(selector))
+    };
+    let path = source
+        .and_then(|e| e.stdin_path.as_ref())
+        .ok_or_else(|| anyhow::anyhow!(i18n.t_format("error_stdin_not_recorded", &[selector])))?;
+    Ok(fs::read(path)?)
+}
+
 fn confirm_delete(i18n: &I18n, skip_confirm_all: &mut bool) -> Result<bool> {
     if *skip_confirm_all {
         return Ok(true);
@@ -762,7 +1433,7 @@ fn shell_quote(arg: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::join_args_for_shell;
+    use super::{join_args_for_shell, RecordFilter};
 
     #[test]
     fn test_join_args_simple() {
@@ -779,11 +1450,7 @@ mod tests {
     #[test]
     fn test_join_args_with_single_quote() {
         let args = vec!["printf".into(), "%s".into(), "it's ok".into()];
-        let expected = ["printf", "%s", r"'it'\''s ok'"]
-            .iter()
-            .copied()
-            .collect::<Vec<_>>()
-            .join(" ");
+        let expected = ["printf", "%s", r"'it'\''s ok'"].join(" ");
         assert_eq!(join_args_for_shell(&args), expected);
     }
 
@@ -793,6 +1460,86 @@ mod tests {
         assert_eq!(join_args_for_shell(&args), "ls -l");
     }
 
+    fn test_record() -> crate::storage::CommandRecord {
+        crate::storage::CommandRecord::for_test("true")
+    }
+
+    #[test]
+    fn test_record_filter_signal() {
+        let mut record = crate::storage::CommandRecord {
+            exit_code: -1,
+            termination: crate::storage::TerminationReason::Killed,
+            signal: Some(11),
+            signal_name: Some("SIGSEGV".to_string()),
+            core_dumped: true,
+            ..test_record()
+        };
+        let filter = |value: &str| RecordFilter {
+            signal: Some(value.to_string()),
+            ..Default::default()
+        };
+        assert!(filter("any").matches(&record));
+        assert!(filter("11").matches(&record));
+        assert!(filter("segv").matches(&record));
+        assert!(filter("SIGSEGV").matches(&record));
+        assert!(!filter("SIGKILL").matches(&record));
+        assert!(RecordFilter::default().matches(&record));
+        record.signal = None;
+        record.signal_name = None;
+        assert!(!filter("any").matches(&record));
+    }
+
+    #[test]
+    fn test_record_filter_git() {
+        let mut record = crate::storage::CommandRecord {
+            git: Some(crate::storage::GitInfo {
+                commit: "1a2b3c4d5e".to_string(),
+                branch: Some("main".to_string()),
+                dirty: false,
+            }),
+            ..test_record()
+        };
+        let commit = |value: &str| RecordFilter {
+            commit: Some(value.to_string()),
+            ..Default::default()
+        };
+        let branch = |value: &str| RecordFilter {
+            branch: Some(value.to_string()),
+            ..Default::default()
+        };
+        assert!(commit("1A2B").matches(&record));
+        assert!(!commit("2b3c").matches(&record));
+        assert!(branch("main").matches(&record));
+        assert!(!branch("dev").matches(&record));
+        record.git = None;
+        assert!(!commit("1a2b").matches(&record));
+        assert!(!branch("main").matches(&record));
+    }
+
+    #[test]
+    fn test_record_filter_host() {
+        let mut record = crate::storage::CommandRecord {
+            host: Some(crate::storage::HostInfo {
+                hostname: "build-01.example.com".to_string(),
+                user: Some("ci".to_string()),
+                os: "linux x86_64".to_string(),
+                kernel: Some("6.1.0".to_string()),
+                dt_version: "1.2.1".to_string(),
+            }),
+            ..test_record()
+        };
+        let host = |value: &str| RecordFilter {
+            host: Some(value.to_string()),
+            ..Default::default()
+        };
+        assert!(host("build-01.example.com").matches(&record));
+        assert!(host("BUILD-01").matches(&record));
+        assert!(!host("build").matches(&record));
+        assert!(!host("laptop").matches(&record));
+        record.host = None;
+        assert!(!host("build-01").matches(&record));
+    }
+
     #[test]
     fn test_join_args_empty_token() {
         let args = vec!["printf".into(), "".into()];
@@ -810,15 +1557,19 @@ fn print_help(i18n: &I18n) {
         println!("{} dt <COMMAND>", i18n.t("help_label_usage"));
         println!();
         println!("{}", i18n.t("help_label_commands"));
-        println!("  {}    {}", "run".green(), i18n.t("help_run"));
-        println!("  {}   {}", "diff".green(), i18n.t("help_diff"));
-        println!("  {}   {}", "show".green(), i18n.t("help_show"));
-        println!("  {}     {}", "ls".green(), i18n.t("help_ls"));
-        println!("  {}  {}", "clean".green(), i18n.t("help_clean"));
-        println!("  {}   {}", "parse".green(), i18n.t("help_parse"));
-        println!("  {} {}", "rebuild".green(), i18n.t("help_rebuild"));
+        println!("  {}       {}", "run".green(), i18n.t("help_run"));
+        println!("  {} {}", "run-batch".green(), i18n.t("help_run_batch"));
+        println!("  {}      {}", "diff".green(), i18n.t("help_diff"));
+        println!("  {}      {}", "show".green(), i18n.t("help_show"));
+        println!("  {}    {}", "replay".green(), i18n.t("help_replay"));
+        println!("  {}     {}", "rerun".green(), i18n.t("help_rerun"));
+        println!("  {}     {}", "watch".green(), i18n.t("help_watch"));
+        println!("  {}        {}", "ls".green(), i18n.t("help_ls"));
+        println!("  {}     {}", "clean".green(), i18n.t("help_clean"));
+        println!("  {}     {}", "parse".green(), i18n.t("help_parse"));
+        println!("  {}   {}", "rebuild".green(), i18n.t("help_rebuild"));
         println!(
-            "  {}   Print this message or the help of the given subcommand(s)",
+            "  {}      Print this message or the help of the given subcommand(s)",
             "help".green()
         );
         println!(
@@ -832,10 +1583,18 @@ fn print_help(i18n: &I18n) {
         println!("  -h, --help           Print help");
         println!("  -v, -V, --version    Print version info");
         println!("      --data-dir <DIR> Override data directory (default: ~/.dt)");
+        println!("      --encoding <ENC> Decode output as ENC instead of detecting it (e.g. latin1, utf-16le)");
         println!();
         println!("{}", i18n.t("help_config_section"));
         println!("  - {}", i18n.t("help_config_tui_mode"));
         println!("  - {}", i18n.t("help_config_alt_screen"));
+        println!("  - {}", i18n.t("help_config_run_pty"));
+        println!("  - {}", i18n.t("help_config_run_kill_grace"));
+        println!("  - {}", i18n.t("help_config_run_env"));
+        println!("  - {}", i18n.t("help_config_run_shell"));
+        println!("  - {}", i18n.t("help_config_run_batch_jobs"));
+        println!("  - {}", i18n.t("help_config_run_capture_git"));
+        println!("  - {}", i18n.t("help_config_storage_compression"));
     } else if args.len() >= 3 && args[1] == "clean" {
         // Clean subcommand's subcommand help
         match args[2].as_str() {
@@ -901,6 +1660,32 @@ fn print_help(i18n: &I18n) {
                     "  -d, --diff-with <TARGET>  {}",
                     i18n.t("help_run_diff_with")
                 );
+                println!("      --pty                 {}", i18n.t("help_run_pty"));
+                println!("      --timeout <DURATION>  {}", i18n.t("help_run_timeout"));
+                println!("      --repeat <N>          {}", i18n.t("help_run_repeat"));
+                println!(
+                    "      --exit-zero           {}",
+                    i18n.t("help_run_exit_zero")
+                );
+                println!(
+                    "      --diff-exit-code[=N]  {}",
+                    i18n.t("help_run_diff_exit_code")
+                );
+                println!("  -q, --quiet               {}", i18n.t("help_run_quiet"));
+                println!(
+                    "      --no-stream           {}",
+                    i18n.t("help_run_no_stream")
+                );
+                println!("      --json                {}", i18n.t("help_run_json"));
+                println!(
+                    "      --on-change <PATHS>   {}",
+                    i18n.t("help_run_on_change")
+                );
+                println!(
+                    "      --debounce <DURATION> {}",
+                    i18n.t("help_run_debounce")
+                );
+                println!("      --track <PATHS>       {}", i18n.t("help_run_track"));
                 println!("  -h, --help  Print help");
                 println!();
                 println!("{}", i18n.t("help_pipeline_tip"));
@@ -939,16 +1724,100 @@ fn print_help(i18n: &I18n) {
                 println!();
                 println!("{}", i18n.t("help_pipeline_tip"));
             }
+            "replay" => {
+                println!("{}", i18n.t("help_replay"));
+                println!();
+                println!(
+                    "{} dt replay [COMMAND] [--code <CODE>] [--speed <N>x]",
+                    i18n.t("help_label_usage")
+                );
+                println!();
+                println!("{}", i18n.t("help_label_arguments"));
+                println!("  [COMMAND]  {}", i18n.t("help_show_command"));
+                println!();
+                println!("{}", i18n.t("help_label_options"));
+                println!("      --code <CODE>  {}", i18n.t("help_replay_code"));
+                println!("      --speed <N>x   {}", i18n.t("help_replay_speed"));
+                println!("  -h, --help         Print help");
+            }
+            "run-batch" => {
+                println!("{}", i18n.t("help_run_batch"));
+                println!();
+                println!(
+                    "{} dt run-batch [OPTIONS] <FILE>",
+                    i18n.t("help_label_usage")
+                );
+                println!();
+                println!("{}", i18n.t("help_label_arguments"));
+                println!("  <FILE>  {}", i18n.t("help_run_batch_file"));
+                println!();
+                println!("{}", i18n.t("help_label_options"));
+                println!(
+                    "  -j, --jobs <N>            {}",
+                    i18n.t("help_run_batch_jobs")
+                );
+                println!(
+                    "      --timeout <DURATION>  {}",
+                    i18n.t("help_run_batch_timeout")
+                );
+                println!("  -h, --help                Print help");
+            }
+            "rerun" => {
+                println!("{}", i18n.t("help_rerun"));
+                println!();
+                println!("{} dt rerun [CODE|RECORD_ID]", i18n.t("help_label_usage"));
+                println!();
+                println!("{}", i18n.t("help_label_arguments"));
+                println!("  [CODE|RECORD_ID]  {}", i18n.t("help_rerun_target"));
+                println!();
+                println!("{}", i18n.t("help_label_options"));
+                println!("  -h, --help        Print help");
+            }
+            "watch" => {
+                println!("{}", i18n.t("help_watch"));
+                println!();
+                println!(
+                    "{} dt watch [OPTIONS] <COMMAND>",
+                    i18n.t("help_label_usage")
+                );
+                println!();
+                println!("{}", i18n.t("help_label_arguments"));
+                println!("  <COMMAND>  {}", i18n.t("help_run_command"));
+                println!();
+                println!("{}", i18n.t("help_label_options"));
+                println!(
+                    "  -n, --interval <DURATION>  {}",
+                    i18n.t("help_watch_interval")
+                );
+                println!(
+                    "      --changes-only         {}",
+                    i18n.t("help_watch_changes_only")
+                );
+                println!(
+                    "      --count <N>            {}",
+                    i18n.t("help_watch_count")
+                );
+                println!("  -h, --help                 Print help");
+                println!();
+                println!("{}", i18n.t("help_pipeline_tip"));
+            }
             "ls" | "list" => {
                 println!("{}", i18n.t("help_ls"));
                 println!();
-                println!("{} dt ls [QUERY] [--json]", i18n.t("help_label_usage"));
+                println!(
+                    "{} dt ls [QUERY] [--json] [--signal <SIG>] [--commit <SHA>] [--branch <NAME>] [--host <NAME>]",
+                    i18n.t("help_label_usage")
+                );
                 println!();
                 println!("{}", i18n.t("help_label_arguments"));
                 println!("  [QUERY]  {}", i18n.t("help_ls_query"));
                 println!();
                 println!("{}", i18n.t("help_label_options"));
                 println!("      --json  {}", i18n.t("help_ls_json"));
+                println!("      --signal <SIG>  {}", i18n.t("help_ls_signal"));
+                println!("      --commit <SHA>  {}", i18n.t("help_ls_commit"));
+                println!("      --branch <NAME> {}", i18n.t("help_ls_branch"));
+                println!("      --host <NAME>   {}", i18n.t("help_ls_host"));
                 println!("  -h, --help  Print help");
             }
             "clean" => {
@@ -1006,8 +1875,74 @@ fn print_help(i18n: &I18n) {
         }
     }
 }
-fn list_records_query(store: &StoreManager, query: &str, _i18n: &I18n, json: bool) -> Result<()> {
+/// Structured filters for `dt ls`, applied on top of the free-text query.
+#[derive(Debug, Default)]
+struct RecordFilter {
+    signal: Option<String>,
+    commit: Option<String>,
+    branch: Option<String>,
+    host: Option<String>,
+}
+
+impl RecordFilter {
+    fn matches(&self, record: &storage::CommandRecord) -> bool {
+        if let Some(wanted) = self.signal.as_deref() {
+            let Some(signal) = record.signal else {
+                return false;
+            };
+            let wanted = wanted.trim();
+            if !wanted.eq_ignore_ascii_case("any") {
+                let matched = match wanted.parse::<i32>() {
+                    Ok(number) => number == signal,
+                    Err(_) => {
+                        let wanted = wanted.to_ascii_uppercase();
+                        let wanted = wanted.strip_prefix("SIG").unwrap_or(&wanted);
+                        record
+                            .signal_name
+                            .as_deref()
+                            .map(|name| name.strip_prefix("SIG").unwrap_or(name) == wanted)
+                            .unwrap_or(false)
+                    }
+                };
+                if !matched {
+                    return false;
+                }
+            }
+        }
+        if let Some(prefix) = self.commit.as_deref() {
+            let prefix = prefix.trim().to_ascii_lowercase();
+            if !record
+                .git
+                .as_ref()
+                .is_some_and(|git| git.commit.starts_with(&prefix))
+            {
+                return false;
+            }
+        }
+        if let Some(branch) = self.branch.as_deref() {
+            let recorded = record.git.as_ref().and_then(|git| git.branch.as_deref());
+            if recorded != Some(branch.trim()) {
+                return false;
+            }
+        }
+        if let Some(host) = self.host.as_deref() {
+            if !record.host.as_ref().is_some_and(|h| h.matches_host(host)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn list_records_query(
+    store: &StoreManager,
+    query: &str,
+    filter: &RecordFilter,
+    _i18n: &I18n,
+    json: bool,
+) -> Result<()> {
     let mut records = store.get_all_records()?;
+    records.retain(|r| filter.matches(r));
     let q = query.trim().to_lowercase();
     if !q.is_empty() {
         fn is_subsequence(needle: &str, haystack: &str) -> bool {
@@ -1044,6 +1979,12 @@ fn list_records_query(store: &StoreManager, query: &str, _i18n: &I18n, json: boo
                     "record_id": r.record_id,
                     "short_code": r.short_code,
                     "working_dir": r.working_dir,
+                    "termination": r.termination,
+                    "signal": r.signal,
+                    "signal_name": r.signal_name,
+                    "core_dumped": r.core_dumped,
+                    "git": r.git,
+                    "host": r.host,
                 })
             })
             .collect();
@@ -1054,16 +1995,38 @@ fn list_records_query(store: &StoreManager, query: &str, _i18n: &I18n, json: boo
                 .timestamp
                 .with_timezone(&chrono::Local)
                 .format("%Y-%m-%d %H:%M:%S");
+            let mut exit = if r.termination == TerminationReason::Completed {
+                r.exit_code.to_string()
+            } else {
+                format!("{}({})", r.exit_code, r.termination)
+            };
+            if let Some(name) = r.signal_name.as_deref() {
+                exit.push_str(&format!(" sig={}", name));
+                if r.core_dumped {
+                    exit.push_str(" core");
+                }
+            } else if let Some(signal) = r.signal {
+                exit.push_str(&format!(" sig={}", signal));
+            }
+            if let Some(git) = &r.git {
+                exit.push_str(&format!(" git={}", git.short_commit()));
+                if let Some(branch) = git.branch.as_deref() {
+                    exit.push_str(&format!("@{}", branch));
+                }
+                if git.dirty {
+                    exit.push('*');
+                }
+            }
+            if let Some(host) = &r.host {
+                exit.push_str(&format!(" host={}", host.label()));
+            }
             if let Some(code) = r.short_code.as_deref() {
                 println!(
                     "{} exit={} dur={}ms [code:{}] {}",
-                    ts, r.exit_code, r.duration_ms, code, r.command
+                    ts, exit, r.duration_ms, code, r.command
                 );
             } else {
-                println!(
-                    "{} exit={} dur={}ms {}",
-                    ts, r.exit_code, r.duration_ms, r.command
-                );
+                println!("{} exit={} dur={}ms {}", ts, exit, r.duration_ms, r.command);
             }
         }
     }
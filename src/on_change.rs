@@ -0,0 +1,269 @@
+use crate::i18n::I18n;
+use anyhow::{anyhow, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// A path or glob given to `dt run --on-change`.
+enum WatchTarget {
+    // Plain file or directory: any change at or below it counts
+    Path(PathBuf),
+    // Glob resolved against the working directory
+    Glob(glob::Pattern),
+}
+
+/// File system watcher that reports debounced changes to the watched paths/globs.
+pub struct ChangeWatcher {
+    _watcher: RecommendedWatcher,
+    rx: Receiver<notify::Result<Event>>,
+    targets: Vec<WatchTarget>,
+    ignored: Vec<PathBuf>,
+    // Content hash of each changed path when it was last looked at (None: not a readable file)
+    seen: RefCell<HashMap<PathBuf, Option<String>>>,
+    // Changes made while the command ran, reported by the next `wait_for_change`
+    pending: RefCell<Vec<PathBuf>>,
+}
+
+impl ChangeWatcher {
+    /// Watch `specs` (paths or globs, relative to the current directory).
+    /// Changes below `ignored` (e.g. the dt data directory) never trigger a run.
+    pub fn new(specs: &[String], ignored: &[PathBuf], i18n: &I18n) -> Result<Self> {
+        let cwd = std::env::current_dir()?;
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        let mut targets = Vec::new();
+
+        for spec in specs {
+            let (base, rest) = split_glob(spec);
+            let base = cwd.join(base);
+            let base = base
+                .canonicalize()
+                .map_err(|_| anyhow!(i18n.t_format("on_change_path_missing", &[spec.as_str()])))?;
+            if base.is_dir() {
+                watcher.watch(&base, RecursiveMode::Recursive)?;
+            } else {
+                // Watch the parent: editors often save by replacing the file.
+                let parent = base.parent().unwrap_or(&base);
+                watcher.watch(parent, RecursiveMode::NonRecursive)?;
+            }
+
+            targets.push(match rest {
+                Some(rest) => {
+                    // Escape the resolved directory so only the user's glob part is special.
+                    let pattern = format!(
+                        "{}{}{}",
+                        glob::Pattern::escape(&base.to_string_lossy()),
+                        std::path::MAIN_SEPARATOR,
+                        rest
+                    );
+                    let pattern = glob::Pattern::new(&pattern).map_err(|e| {
+                        anyhow!(
+                            i18n.t_format("on_change_bad_glob", &[spec.as_str(), &e.to_string()])
+                        )
+                    })?;
+                    WatchTarget::Glob(pattern)
+                }
+                None => WatchTarget::Path(base),
+            });
+        }
+
+        let ignored = ignored
+            .iter()
+            .map(|p| p.canonicalize().unwrap_or_else(|_| p.clone()))
+            .collect();
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            targets,
+            ignored,
+            seen: RefCell::default(),
+            pending: RefCell::default(),
+        })
+    }
+
+    /// Block until a watched path changes, then keep collecting events until none
+    /// arrive for `debounce`. Changes made during the last run are returned straight
+    /// away. Returns the changed paths, or `None` if the watcher stopped.
+    pub fn wait_for_change(&self, debounce: Duration) -> Option<Vec<PathBuf>> {
+        let mut changed = self.pending.take();
+        while changed.is_empty() {
+            let mut paths = Vec::new();
+            self.collect(self.rx.recv().ok()?, &mut paths);
+            if !paths.is_empty() {
+                self.collect_until_quiet(debounce, &mut paths);
+                changed = self.content_changed(paths);
+            }
+        }
+        Some(changed)
+    }
+
+    /// Take in the changes made while a run was going on, waiting until none arrive
+    /// for `debounce`. Files the command rewrote with the content they had when dt
+    /// last saw them are dropped; any other change (e.g. a file the user saved
+    /// during a long build) makes the next `wait_for_change` return at once.
+    pub fn run_finished(&self, debounce: Duration) {
+        let mut paths = Vec::new();
+        self.collect_until_quiet(debounce, &mut paths);
+        let changed = self.content_changed(paths);
+        self.pending.borrow_mut().extend(changed);
+    }
+
+    fn collect_until_quiet(&self, debounce: Duration, paths: &mut Vec<PathBuf>) {
+        loop {
+            match self.rx.recv_timeout(debounce) {
+                Ok(event) => self.collect(event, paths),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+
+    /// Keep the paths whose content differs from when they were last looked at.
+    /// Paths not seen before always count.
+    fn content_changed(&self, mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        paths.sort();
+        paths.dedup();
+        let mut seen = self.seen.borrow_mut();
+        paths.retain(|path| {
+            let hash = crate::storage::hash_file(path).ok();
+            seen.insert(path.clone(), hash.clone()) != Some(hash)
+        });
+        paths
+    }
+
+    fn collect(&self, event: notify::Result<Event>, changed: &mut Vec<PathBuf>) {
+        let Ok(event) = event else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        changed.extend(event.paths.into_iter().filter(|p| self.is_watched(p)));
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        if self.ignored.iter().any(|dir| path.starts_with(dir)) {
+            return false;
+        }
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        self.targets.iter().any(|target| match target {
+            WatchTarget::Path(base) => path.starts_with(base),
+            WatchTarget::Glob(pattern) => pattern.matches_path_with(path, options),
+        })
+    }
+}
+
+/// Split `src/**/*.rs` into the directory to watch (`src`) and the glob part
+/// below it (`**/*.rs`). Plain paths have no glob part.
+fn split_glob(spec: &str) -> (PathBuf, Option<String>) {
+    let mut base = PathBuf::new();
+    let mut components = Path::new(spec).components();
+    for component in components.by_ref() {
+        let text = component.as_os_str().to_string_lossy();
+        if matches!(component, Component::Normal(_)) && text.contains(['*', '?', '[']) {
+            let mut rest = PathBuf::from(text.as_ref());
+            rest.extend(components);
+            return (
+                dot_if_empty(base),
+                Some(rest.to_string_lossy().into_owned()),
+            );
+        }
+        base.push(component);
+    }
+    (dot_if_empty(base), None)
+}
+
+fn dot_if_empty(path: PathBuf) -> PathBuf {
+    if path.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn split_glob_finds_static_prefix() {
+        assert_eq!(
+            split_glob("src/**/*.rs"),
+            (PathBuf::from("src"), Some("**/*.rs".to_string()))
+        );
+        assert_eq!(
+            split_glob("*.toml"),
+            (PathBuf::from("."), Some("*.toml".to_string()))
+        );
+        assert_eq!(
+            split_glob("Cargo.toml"),
+            (PathBuf::from("Cargo.toml"), None)
+        );
+    }
+
+    #[test]
+    fn glob_change_is_reported_and_others_ignored() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let i18n = I18n::new("en");
+        let spec = format!("{}/*.txt", dir.path().display());
+        let watcher = ChangeWatcher::new(&[spec], &[], &i18n).unwrap();
+
+        fs::write(dir.path().join("skip.log"), "x").unwrap();
+        fs::write(dir.path().join("hit.txt"), "x").unwrap();
+        let changed = watcher.wait_for_change(Duration::from_millis(200)).unwrap();
+        let names: Vec<_> = changed
+            .iter()
+            .filter_map(|p| p.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .collect();
+        assert!(names.iter().all(|n| n == "hit.txt"), "{:?}", names);
+        assert!(!names.is_empty());
+    }
+
+    #[test]
+    fn rewrites_by_the_run_are_dropped_but_edits_during_it_are_kept() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let i18n = I18n::new("en");
+        let spec = dir.path().display().to_string();
+        let watcher = ChangeWatcher::new(&[spec], &[], &i18n).unwrap();
+        let debounce = Duration::from_millis(200);
+        let names = |changed: Vec<PathBuf>| -> Vec<String> {
+            changed
+                .iter()
+                .filter_map(|p| p.file_name())
+                .map(|n| n.to_string_lossy().into_owned())
+                .collect()
+        };
+
+        fs::write(dir.path().join("out.txt"), "1").unwrap();
+        assert_eq!(
+            names(watcher.wait_for_change(debounce).unwrap()),
+            ["out.txt"]
+        );
+
+        // A run that rewrites its output and, meanwhile, the user saves a file.
+        fs::write(dir.path().join("out.txt"), "1").unwrap();
+        fs::write(dir.path().join("edit.txt"), "x").unwrap();
+        watcher.run_finished(debounce);
+        assert_eq!(
+            names(watcher.wait_for_change(debounce).unwrap()),
+            ["edit.txt"]
+        );
+
+        // A run that only rewrites its output is followed by nothing.
+        fs::write(dir.path().join("out.txt"), "1").unwrap();
+        watcher.run_finished(debounce);
+        fs::write(dir.path().join("after.txt"), "x").unwrap();
+        assert_eq!(
+            names(watcher.wait_for_change(debounce).unwrap()),
+            ["after.txt"]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::storage::{CommandExecution, OutputStream};
+use anyhow::Result;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Parse a playback speed such as `2x`, `0.5x` or `3`.
+pub fn parse_speed(value: &str) -> Result<f64, String> {
+    let trimmed = value.trim();
+    let number = trimmed
+        .strip_suffix('x')
+        .or_else(|| trimmed.strip_suffix('X'))
+        .unwrap_or(trimmed);
+    match number.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err(format!("Invalid speed '{}'. Examples: 1x, 2x, 0.5x", value)),
+    }
+}
+
+/// Re-emit a recorded run on the terminal with its original output timing.
+pub fn replay(execution: &CommandExecution, speed: f64) -> Result<()> {
+    let stdout = io::stdout();
+    let stderr = io::stderr();
+    replay_to(
+        execution,
+        speed,
+        &mut stdout.lock(),
+        &mut stderr.lock(),
+        thread::sleep,
+    )
+}
+
+/// Replay into arbitrary writers. Runs without a recorded timeline are written
+/// out at once (stdout, then stderr).
+fn replay_to<O: Write, E: Write, S: FnMut(Duration)>(
+    execution: &CommandExecution,
+    speed: f64,
+    out: &mut O,
+    err: &mut E,
+    mut sleep: S,
+) -> Result<()> {
+    let stdout = execution.stdout_bytes();
+    let stderr = execution.stderr_bytes();
+
+    let Some(timeline) = execution.timeline.as_ref() else {
+        out.write_all(stdout)?;
+        out.flush()?;
+        err.write_all(stderr)?;
+        err.flush()?;
+        return Ok(());
+    };
+
+    let mut last_us = 0u64;
+    for chunk in timeline {
+        let wait_us = chunk.elapsed_us.saturating_sub(last_us);
+        last_us = chunk.elapsed_us;
+        if wait_us > 0 {
+            sleep(Duration::from_secs_f64(
+                wait_us as f64 / 1_000_000.0 / speed,
+            ));
+        }
+
+        let (bytes, writer): (&[u8], &mut dyn Write) = match chunk.stream {
+            OutputStream::Stdout => (stdout, out),
+            OutputStream::Stderr => (stderr, err),
+        };
+        for (start, end) in execution.view_ranges(chunk.stream, chunk.offset, chunk.len) {
+            writer.write_all(&bytes[start..end])?;
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::TimelineChunk;
+
+    fn chunk(stream: OutputStream, elapsed_us: u64, offset: u64, len: u64) -> TimelineChunk {
+        TimelineChunk {
+            stream,
+            elapsed_us,
+            offset,
+            len,
+        }
+    }
+
+    #[test]
+    fn parse_speed_accepts_multipliers() {
+        assert_eq!(parse_speed("2x").unwrap(), 2.0);
+        assert_eq!(parse_speed("0.5").unwrap(), 0.5);
+        assert!(parse_speed("0x").is_err());
+        assert!(parse_speed("fast").is_err());
+    }
+
+    #[test]
+    fn replay_preserves_order_and_scales_delays() {
+        let execution = CommandExecution {
+            stdout: "one\nthree\n".to_string(),
+            stderr: "two\n".to_string(),
+            timeline: Some(vec![
+                chunk(OutputStream::Stdout, 1_000, 0, 4),
+                chunk(OutputStream::Stderr, 401_000, 0, 4),
+                chunk(OutputStream::Stdout, 1_001_000, 4, 6),
+            ]),
+            ..test_execution()
+        };
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        let mut sleeps = Vec::new();
+        replay_to(&execution, 2.0, &mut out, &mut err, |d| sleeps.push(d)).unwrap();
+
+        assert_eq!(out, b"one\nthree\n");
+        assert_eq!(err, b"two\n");
+        assert_eq!(
+            sleeps,
+            vec![
+                Duration::from_micros(500),
+                Duration::from_millis(200),
+                Duration::from_millis(300),
+            ]
+        );
+    }
+
+    fn test_execution() -> CommandExecution {
+        CommandExecution::for_test(crate::storage::CommandRecord::for_test("demo"), "")
+    }
+}
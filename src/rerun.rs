@@ -0,0 +1,141 @@
+use crate::executor::{self, CommandExecutor, ExecOptions, StdinSource};
+use crate::i18n::I18n;
+use crate::storage::CommandExecution;
+use crate::store_manager::StoreManager;
+use anyhow::{anyhow, Result};
+use std::fs;
+
+/// Options for re-running `original` the way it was recorded: same working
+/// directory, shell and PTY mode, the recorded stdin when there is one, and
+/// the same tracked files.
+pub fn options_for(
+    original: &CommandExecution,
+    base: &ExecOptions,
+    i18n: &I18n,
+) -> Result<ExecOptions> {
+    let record = &original.record;
+    if !record.working_dir.is_dir() {
+        return Err(anyhow!(i18n.t_format(
+            "rerun_missing_dir",
+            &[&record.working_dir.display().to_string()]
+        )));
+    }
+    let mut options = base.clone();
+    options.working_dir = Some(record.working_dir.clone());
+    options.pty = record.pty;
+    if let Some(argv) = &record.shell_argv {
+        options.shell = argv.clone();
+    } else if let Some(shell) = record.shell.as_deref() {
+        options.shell = executor::parse_shell(shell, i18n)?;
+    } else {
+        // Recorded before `--shell` existed, so it ran under the default shell.
+        options.shell = vec![executor::DEFAULT_SHELL.to_string()];
+    }
+    if let Some(tracked) = &record.artifacts {
+        options.track = tracked.specs.clone();
+    }
+    if let Some(path) = &original.stdin_path {
+        options.stdin = StdinSource::Replay(fs::read(path)?);
+    }
+    Ok(options)
+}
+
+/// Execute `original`'s command again in its recorded context and give the new
+/// run a short code. The caller reports and saves it.
+pub fn rerun(
+    original: &CommandExecution,
+    base: &ExecOptions,
+    store: &StoreManager,
+    i18n: &I18n,
+) -> Result<CommandExecution> {
+    let options = options_for(original, base, i18n)?;
+    let mut execution = CommandExecutor::execute(original.record.command_to_run(), &options, i18n)?;
+    store.assign_short_code(&mut execution.record, i18n)?;
+    Ok(execution)
+}
+
+/// Re-run action for the interactive selector: output is not echoed (the
+/// selector owns the terminal) and the new run is saved straight away.
+pub fn quiet_action<'a>(
+    base: &'a ExecOptions,
+    store: &'a StoreManager,
+    i18n: &'a I18n,
+) -> impl FnMut(&CommandExecution) -> Result<CommandExecution> + 'a {
+    move |original| {
+        let options = ExecOptions {
+            quiet: true,
+            stdin: StdinSource::Null,
+            ..base.clone()
+        };
+        let mut execution = rerun(original, &options, store, i18n)?;
+        store.save_execution(&mut execution, i18n)?;
+        Ok(execution)
+    }
+}
+
+/// Find the run `dt rerun` refers to: an exact record id, or a short code.
+/// Short codes are per command, so among matches prefer runs recorded in the
+/// current directory, then the most recent.
+pub fn resolve_target(store: &StoreManager, target: &str, i18n: &I18n) -> Result<CommandExecution> {
+    let records = store.get_all_records()?;
+    let cwd = std::env::current_dir().ok();
+    let record = records
+        .iter()
+        .find(|r| r.record_id == target)
+        .or_else(|| {
+            records
+                .iter()
+                .filter(|r| r.short_code.as_deref() == Some(target))
+                .max_by_key(|r| (Some(&r.working_dir) == cwd.as_ref(), r.timestamp))
+        })
+        .ok_or_else(|| anyhow!(i18n.t_format("rerun_target_not_found", &[target])))?;
+    store.load_execution(record, i18n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::CommandRecord;
+    use std::path::PathBuf;
+
+    fn recorded(working_dir: PathBuf) -> CommandExecution {
+        let record = CommandRecord {
+            working_dir,
+            short_code: Some("a".to_string()),
+            pty: true,
+            shell: Some("bash -o pipefail".to_string()),
+            shell_argv: Some(vec!["bash".into(), "-o".into(), "pipefail".into()]),
+            ..CommandRecord::for_test("pwd")
+        };
+        CommandExecution::for_test(record, "")
+    }
+
+    #[test]
+    fn options_follow_the_recorded_context() {
+        let i18n = I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let original = recorded(dir.path().to_path_buf());
+        let options = options_for(&original, &ExecOptions::default(), &i18n).unwrap();
+        assert_eq!(options.working_dir.as_deref(), Some(dir.path()));
+        assert!(options.pty);
+        assert_eq!(options.shell, vec!["bash", "-o", "pipefail"]);
+        assert!(matches!(options.stdin, StdinSource::Inherit));
+
+        // Records from before shell_argv existed only have the joined form.
+        let mut legacy = recorded(dir.path().to_path_buf());
+        legacy.record.shell_argv = None;
+        legacy.record.shell = Some("env 'A=1 2' sh".to_string());
+        let options = options_for(&legacy, &ExecOptions::default(), &i18n).unwrap();
+        assert_eq!(options.shell, vec!["env", "A=1 2", "sh"]);
+        legacy.record.shell = None;
+        let configured = ExecOptions {
+            shell: vec!["bash".to_string()],
+            ..Default::default()
+        };
+        let options = options_for(&legacy, &configured, &i18n).unwrap();
+        assert_eq!(options.shell, vec!["sh"]);
+
+        let gone = recorded(dir.path().join("missing"));
+        assert!(options_for(&gone, &ExecOptions::default(), &i18n).is_err());
+    }
+}
@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 pub fn format_command(command: &str) -> String {
     // Remove leading and trailing whitespace and quotes
@@ -64,6 +67,50 @@ pub fn hash_command(command: &str) -> String {
     hex::encode(hasher.finalize())
 }
 
+/// Collision-free key for a run recorded at `timestamp`, used in its record id
+/// (`<command_hash>_<key>`) and file names (`meta_<key>.json`, ...).
+///
+/// Keys are zero-padded nanoseconds since the epoch, so they sort by time, plus
+/// the id of the recording process. Within one process keys strictly increase,
+/// and no two running processes share an id, so keys never repeat while either
+/// process is alive.
+pub fn new_record_key(timestamp: &DateTime<Utc>) -> String {
+    use std::sync::atomic::{AtomicI64, Ordering};
+    static LAST: AtomicI64 = AtomicI64::new(0);
+
+    let now = timestamp.timestamp_nanos_opt().unwrap_or_default();
+    let previous = LAST
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+            Some(now.max(last + 1))
+        })
+        .unwrap_or_default();
+    let nanos = now.max(previous + 1);
+    format!("{:019}-{:x}", nanos, std::process::id())
+}
+
+/// Position of a record key in time, in nanoseconds. Keys of records saved
+/// before sub-second ids are plain Unix seconds.
+pub fn record_key_nanos(key: &str) -> Option<i64> {
+    match key.split_once('-') {
+        Some((nanos, _)) => nanos.parse().ok(),
+        None => key.parse::<i64>().ok()?.checked_mul(1_000_000_000),
+    }
+}
+
+/// SHA256 of a file's contents, read in chunks so large files are never held in memory.
+pub fn hash_file(path: &std::path::Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// SHA256 of arbitrary bytes (used for recorded stdin).
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hex::encode(hasher.finalize())
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CommandRecord {
     pub command: String,          // Command executed
@@ -72,9 +119,367 @@ pub struct CommandRecord {
     pub working_dir: PathBuf,     // Working directory
     pub exit_code: i32,           // Exit code
     pub duration_ms: u64,         // Execution duration (milliseconds)
-    pub record_id: String,        // Record unique identifier
+    pub record_id: String,        // `<command_hash>_<key>`, see `new_record_key`
     #[serde(default)]
     pub short_code: Option<String>, // Short code for quick reference (per-command)
+    #[serde(default)]
+    pub pty: bool, // Output was captured through a pseudo-terminal
+    #[serde(default)]
+    pub termination: TerminationReason, // How the command ended
+    #[serde(default)]
+    pub signal: Option<i32>, // Signal that terminated the process (unix only)
+    #[serde(default)]
+    pub signal_name: Option<String>, // Symbolic name of `signal`, e.g. "SIGSEGV"
+    #[serde(default)]
+    pub core_dumped: bool, // The terminating signal produced a core dump
+    #[serde(default)]
+    pub resources: Option<ResourceUsage>, // CPU/memory accounting from wait4 (unix only)
+    #[serde(default)]
+    pub stdout_encoding: Option<String>, // Text encoding of stdout (detected or forced)
+    #[serde(default)]
+    pub stderr_encoding: Option<String>, // Text encoding of stderr (detected or forced)
+    #[serde(default)]
+    pub stdin_hash: Option<String>, // SHA256 of recorded stdin (None when stdin was not recorded)
+    #[serde(default)]
+    pub stdin_size: Option<u64>, // Recorded stdin size in bytes
+    #[serde(default)]
+    pub env: Option<BTreeMap<String, String>>, // Captured environment (run.env_allowlist/denylist)
+    #[serde(default)]
+    pub shell: Option<String>, // Shell invocation used, e.g. "bash -o pipefail" (None: legacy "sh")
+    #[serde(default)]
+    pub shell_argv: Option<Vec<String>>, // `shell` as program and arguments (absent in older records)
+    #[serde(default)]
+    pub raw_command: Option<String>, // Command exactly as passed to the shell; `command` is normalized
+    #[serde(default)]
+    pub stdout_truncation: Option<OutputTruncation>, // Set when stdout exceeded storage.max_output_bytes
+    #[serde(default)]
+    pub stderr_truncation: Option<OutputTruncation>, // Set when stderr exceeded storage.max_output_bytes
+    #[serde(default)]
+    pub git: Option<GitInfo>, // Repository state when the command started inside a git work tree
+    #[serde(default)]
+    pub artifacts: Option<TrackedArtifacts>, // Files snapshotted after the run (`dt run --track`)
+    #[serde(default)]
+    pub host: Option<HostInfo>, // Machine and user that recorded the run (absent in older records)
+    #[serde(default)]
+    pub stdout_sha256: Option<String>, // Blob holding the stored stdout (older records keep stdout_<ts>.txt)
+    #[serde(default)]
+    pub stderr_sha256: Option<String>, // Blob holding the stored stderr
+    #[serde(default)]
+    pub stdout_codec: Compression, // How the stdout blob is compressed (older records: none)
+    #[serde(default)]
+    pub stderr_codec: Compression, // How the stderr blob is compressed
+}
+
+/// Compression applied to a stored output blob (`[storage] compression`).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+/// Where a run was recorded, so histories shared between machines stay tellable apart.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct HostInfo {
+    pub hostname: String,
+    #[serde(default)]
+    pub user: Option<String>,
+    pub os: String, // OS and architecture, e.g. `linux x86_64`
+    #[serde(default)]
+    pub kernel: Option<String>, // Kernel release on unix
+    pub dt_version: String,
+}
+
+impl HostInfo {
+    /// `user@hostname`, or just the hostname when the user is unknown.
+    pub fn label(&self) -> String {
+        match &self.user {
+            Some(user) => format!("{}@{}", user, self.hostname),
+            None => self.hostname.clone(),
+        }
+    }
+
+    /// Label plus platform details, e.g. `ci@build-01 (linux x86_64, kernel 6.1.0, dt 1.2.1)`.
+    pub fn describe(&self) -> String {
+        let mut details = vec![self.os.clone()];
+        if let Some(kernel) = &self.kernel {
+            details.push(format!("kernel {}", kernel));
+        }
+        details.push(format!("dt {}", self.dt_version));
+        format!("{} ({})", self.label(), details.join(", "))
+    }
+
+    /// Whether `name` is this host: the full hostname or its first label,
+    /// ignoring case (`build-01` matches `build-01.example.com`).
+    pub fn matches_host(&self, name: &str) -> bool {
+        let name = name.trim();
+        self.hostname.eq_ignore_ascii_case(name)
+            || self
+                .hostname
+                .split('.')
+                .next()
+                .is_some_and(|short| short.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Files a run was asked to track and what they contained once it finished.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct TrackedArtifacts {
+    pub specs: Vec<String>,      // Paths/globs as given to --track
+    pub files: Vec<TrackedFile>, // Matching files sorted by path; file N is stored as artifacts_<ts>/N
+}
+
+/// One tracked file in a run's snapshot.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TrackedFile {
+    pub path: PathBuf,  // Relative to the working directory when inside it
+    pub size: u64,      // Size in bytes
+    pub sha256: String, // SHA256 of the contents
+}
+
+/// Git state of the working directory when a command started.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GitInfo {
+    pub commit: String, // Full HEAD commit hash
+    #[serde(default)]
+    pub branch: Option<String>, // Checked-out branch (None when HEAD is detached)
+    #[serde(default)]
+    pub dirty: bool, // Tracked changes or untracked files were present
+}
+
+impl GitInfo {
+    /// Abbreviated commit hash, as shown by `git log --oneline`.
+    pub fn short_commit(&self) -> &str {
+        &self.commit[..self.commit.len().min(7)]
+    }
+
+    /// Compact description, e.g. `1a2b3c4 (main, dirty)`.
+    pub fn label(&self) -> String {
+        let mut details: Vec<&str> = self.branch.iter().map(String::as_str).collect();
+        if self.dirty {
+            details.push("dirty");
+        }
+        if details.is_empty() {
+            self.short_commit().to_string()
+        } else {
+            format!("{} ({})", self.short_commit(), details.join(", "))
+        }
+    }
+}
+
+/// Decode one stored output stream, marking where truncated output was cut.
+/// `view` says how the bytes in memory cut the stored stream (see `read_view`);
+/// being smaller than the stored head and tail, a view also hides where
+/// `truncation` cut the original.
+pub fn decode_output(
+    bytes: &[u8],
+    view: Option<&OutputTruncation>,
+    truncation: Option<&OutputTruncation>,
+    decode: impl Fn(&[u8]) -> String,
+    i18n: &crate::i18n::I18n,
+) -> String {
+    if let Some(view) = view {
+        let total = truncation.map_or(view.total_bytes, |t| t.total_bytes);
+        let omitted = total - view.head_bytes - view.tail_bytes;
+        let marker = i18n.t_format("output_view_marker", &[&omitted.to_string()]);
+        return view.decode_with_marker(bytes, decode, &marker);
+    }
+    match truncation {
+        Some(truncation) => {
+            let marker = i18n.t_format(
+                "output_truncated_marker",
+                &[&truncation.omitted_bytes().to_string()],
+            );
+            truncation.decode_with_marker(bytes, decode, &marker)
+        }
+        None => decode(bytes),
+    }
+}
+
+/// Bytes of each stored output stream held in memory for display and diffs.
+/// Longer streams are viewed as their first and last halves; hashing and
+/// storing always work on the whole stream on disk.
+pub const MAX_VIEW_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Read at most `limit` bytes of a stream: all of it when it fits, otherwise
+/// its first and last halves, with how the middle was cut.
+pub fn read_view(
+    mut reader: impl std::io::Read,
+    limit: u64,
+) -> std::io::Result<(Vec<u8>, Option<OutputTruncation>)> {
+    use std::io::Read;
+    let head_limit = limit.div_ceil(2);
+    let tail_limit = (limit - head_limit) as usize;
+    let mut head = Vec::new();
+    (&mut reader).take(head_limit).read_to_end(&mut head)?;
+
+    let mut tail = std::collections::VecDeque::with_capacity(tail_limit);
+    let mut total = head.len() as u64;
+    let mut chunk = [0u8; 64 * 1024];
+    loop {
+        let n = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        total += n as u64;
+        let keep = &chunk[n.saturating_sub(tail_limit)..n];
+        tail.extend(keep.iter().copied());
+        let excess = tail.len().saturating_sub(tail_limit);
+        tail.drain(..excess);
+    }
+
+    let truncation = (total > (head.len() + tail.len()) as u64).then_some(OutputTruncation {
+        total_bytes: total,
+        head_bytes: head.len() as u64,
+        tail_bytes: tail.len() as u64,
+    });
+    head.extend(tail);
+    Ok((head, truncation))
+}
+
+/// How an output stream larger than `storage.max_output_bytes` was cut down:
+/// the first `head_bytes` and the last `tail_bytes` are stored back to back.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct OutputTruncation {
+    pub total_bytes: u64, // Bytes the command actually wrote
+    pub head_bytes: u64,  // Stored prefix
+    pub tail_bytes: u64,  // Stored suffix
+}
+
+impl OutputTruncation {
+    pub fn omitted_bytes(&self) -> u64 {
+        self.total_bytes - self.head_bytes - self.tail_bytes
+    }
+
+    /// Map a byte range of the original stream onto the stored file.
+    /// Parts that fell into the omitted middle are dropped.
+    pub fn stored_ranges(&self, offset: u64, len: u64) -> Vec<(u64, u64)> {
+        let end = offset + len;
+        let tail_start = self.total_bytes - self.tail_bytes;
+        let mut ranges = Vec::new();
+        if offset < self.head_bytes {
+            ranges.push((offset, end.min(self.head_bytes) - offset));
+        }
+        if end > tail_start {
+            let start = offset.max(tail_start);
+            ranges.push((self.head_bytes + start - tail_start, end - start));
+        }
+        ranges
+    }
+
+    /// Decode stored output, putting `marker` on its own line where the middle was cut.
+    pub fn decode_with_marker(
+        &self,
+        bytes: &[u8],
+        decode: impl Fn(&[u8]) -> String,
+        marker: &str,
+    ) -> String {
+        let split = (self.head_bytes as usize).min(bytes.len());
+        let mut text = decode(&bytes[..split]);
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(marker);
+        text.push('\n');
+        text.push_str(&decode(&bytes[split..]));
+        text
+    }
+}
+
+/// Resource accounting for a finished process tree, as reported by `wait4`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    pub user_cpu_ms: u64,              // User-mode CPU time
+    pub sys_cpu_ms: u64,               // Kernel-mode CPU time
+    pub max_rss_kb: u64,               // Peak resident set size (KiB)
+    pub voluntary_ctx_switches: u64,   // Blocking waits (I/O, sleep, ...)
+    pub involuntary_ctx_switches: u64, // Preemptions by the scheduler
+}
+
+impl ResourceUsage {
+    pub fn cpu_ms(&self) -> u64 {
+        self.user_cpu_ms + self.sys_cpu_ms
+    }
+
+    pub fn ctx_switches(&self) -> u64 {
+        self.voluntary_ctx_switches + self.involuntary_ctx_switches
+    }
+}
+
+impl CommandRecord {
+    /// Key shared by the record's files (`meta_<key>.json`, `timeline_<key>.json`,
+    /// ...): the part of the record id after the command hash.
+    pub fn file_key(&self) -> String {
+        match self.record_id.split_once('_') {
+            Some((_, key)) if !key.is_empty() => key.to_string(),
+            _ => self.timestamp.timestamp().to_string(),
+        }
+    }
+
+    /// Command to pass to the shell to run this again. `command` is normalized for
+    /// grouping (quotes and spacing may differ), so older records only approximate it.
+    pub fn command_to_run(&self) -> &str {
+        self.raw_command.as_deref().unwrap_or(&self.command)
+    }
+
+    /// Shell the command ran under; records from before `--shell` existed used `sh`.
+    pub fn shell_label(&self) -> &str {
+        self.shell.as_deref().unwrap_or("sh")
+    }
+
+    /// Short description of the recorded stdin, e.g. `42 bytes, sha 1a2b3c4d`.
+    pub fn stdin_label(&self) -> Option<String> {
+        let hash = self.stdin_hash.as_deref()?;
+        Some(format!(
+            "{} bytes, sha {}",
+            self.stdin_size.unwrap_or(0),
+            &hash[..hash.len().min(8)]
+        ))
+    }
+
+    /// Human readable description of the terminating signal, e.g. `SIGSEGV (11, core dumped)`.
+    pub fn signal_label(&self) -> Option<String> {
+        let signal = self.signal?;
+        let name = self.signal_name.as_deref().unwrap_or("signal");
+        if self.core_dumped {
+            Some(format!("{} ({}, core dumped)", name, signal))
+        } else {
+            Some(format!("{} ({})", name, signal))
+        }
+    }
+}
+
+/// How a recorded command ended.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminationReason {
+    /// The command exited on its own.
+    #[default]
+    Completed,
+    /// dt stopped the command after `--timeout` elapsed.
+    TimedOut,
+    /// The command was terminated by a signal dt did not send.
+    Killed,
+}
+
+impl TerminationReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Completed => "completed",
+            Self::TimedOut => "timed_out",
+            Self::Killed => "killed",
+        }
+    }
+}
+
+impl fmt::Display for TerminationReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -88,6 +493,219 @@ pub struct CommandExecution {
     pub streamed_stdout: bool, // Indicates stdout was streamed live during execution
     #[serde(skip)]
     pub streamed_stderr: bool, // Indicates stderr was streamed live during execution
+    #[serde(skip)]
+    pub stdout_raw: Vec<u8>, // Exact stdout bytes (stdout holds the decoded text)
+    #[serde(skip)]
+    pub stderr_raw: Vec<u8>, // Exact stderr bytes (stderr holds the decoded text)
+    #[serde(skip)]
+    pub stdout_view: Option<OutputTruncation>, // Set when stdout_raw holds only the start and end of the stored stdout (see MAX_VIEW_BYTES)
+    #[serde(skip)]
+    pub stderr_view: Option<OutputTruncation>, // Set when stderr_raw holds only the start and end of the stored stderr
+    #[serde(default)]
+    pub stdin_path: Option<PathBuf>, // Stored stdin file path (when stdin was recorded)
+    #[serde(skip)]
+    pub stdin_raw: Option<Vec<u8>>, // Stdin delivered to the command, pending save
+    #[serde(skip)]
+    pub timeline: Option<Vec<TimelineChunk>>, // Interleaving of stdout/stderr (None: not recorded)
+    #[serde(skip)]
+    pub stdout_staged: Option<Arc<tempfile::TempPath>>, // stdout streamed to disk during the run, pending save
+    #[serde(skip)]
+    pub stderr_staged: Option<Arc<tempfile::TempPath>>, // stderr streamed to disk during the run, pending save
+    #[serde(skip)]
+    pub artifacts_dir: Option<PathBuf>, // Snapshot of the tracked files (file N of `record.artifacts`)
+    #[serde(skip)]
+    pub artifacts_staged: Option<Arc<tempfile::TempDir>>, // Snapshot taken during the run, pending save
+}
+
+/// Which output stream a timeline chunk came from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// One read from the command's stdout or stderr, in arrival order.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TimelineChunk {
+    pub stream: OutputStream, // Source stream
+    pub elapsed_us: u64,      // Microseconds since the command started
+    pub offset: u64,          // Byte offset into the stream's stored output
+    pub len: u64,             // Chunk length in bytes
+}
+
+impl CommandExecution {
+    /// Snapshot contents of tracked file `index` (see `CommandRecord::artifacts`).
+    pub fn artifact_bytes(&self, index: usize) -> Option<Vec<u8>> {
+        let dir = self.artifacts_dir.as_ref()?;
+        std::fs::read(dir.join(index.to_string())).ok()
+    }
+
+    /// Bytes to persist for stdout; falls back to the text when no raw capture exists.
+    pub fn stdout_bytes(&self) -> &[u8] {
+        if self.stdout_raw.is_empty() {
+            self.stdout.as_bytes()
+        } else {
+            &self.stdout_raw
+        }
+    }
+
+    /// Size of the stored stdout, which `stdout_raw` may only show part of.
+    pub fn stdout_len(&self) -> u64 {
+        self.stdout_view
+            .map_or(self.stdout_bytes().len() as u64, |view| view.total_bytes)
+    }
+
+    /// Size of the stored stderr, which `stderr_raw` may only show part of.
+    pub fn stderr_len(&self) -> u64 {
+        self.stderr_view
+            .map_or(self.stderr_bytes().len() as u64, |view| view.total_bytes)
+    }
+
+    /// Ranges of the in-memory bytes of `stream` holding a range of the stored stream
+    /// (timeline offsets point into the stored stream). Parts the view omits are dropped.
+    pub fn view_ranges(&self, stream: OutputStream, offset: u64, len: u64) -> Vec<(usize, usize)> {
+        let (bytes, view) = match stream {
+            OutputStream::Stdout => (self.stdout_bytes(), self.stdout_view),
+            OutputStream::Stderr => (self.stderr_bytes(), self.stderr_view),
+        };
+        let ranges = match view {
+            Some(view) => view.stored_ranges(offset, len),
+            None => vec![(offset, len)],
+        };
+        ranges
+            .into_iter()
+            .map(|(offset, len)| {
+                let end = ((offset + len) as usize).min(bytes.len());
+                ((offset as usize).min(end), end)
+            })
+            .collect()
+    }
+
+    /// stdout and stderr interleaved in the order they were produced.
+    /// `None` when the run has no recorded timeline.
+    pub fn combined_output(&self) -> Option<String> {
+        let timeline = self.timeline.as_ref()?;
+        let stdout = self.stdout_bytes();
+        let stderr = self.stderr_bytes();
+        let stdout_encoding = self.record.stdout_encoding.as_deref();
+        let stderr_encoding = self.record.stderr_encoding.as_deref();
+
+        let mut combined = String::new();
+        // Merge consecutive chunks of the same stream so multi-byte characters
+        // split across reads still decode correctly.
+        let mut run: Option<(OutputStream, usize, usize)> = None;
+        let mut flush = |run: (OutputStream, usize, usize)| {
+            let (stream, start, end) = run;
+            let (bytes, encoding) = match stream {
+                OutputStream::Stdout => (stdout, stdout_encoding),
+                OutputStream::Stderr => (stderr, stderr_encoding),
+            };
+            let end = end.min(bytes.len());
+            let start = start.min(end);
+            combined.push_str(&crate::encoding::decode_stored(
+                &bytes[start..end],
+                encoding,
+                None,
+            ));
+        };
+        for chunk in timeline {
+            for (start, end) in self.view_ranges(chunk.stream, chunk.offset, chunk.len) {
+                run = match run {
+                    Some((stream, run_start, run_end))
+                        if stream == chunk.stream && run_end == start =>
+                    {
+                        Some((stream, run_start, end))
+                    }
+                    Some(previous) => {
+                        flush(previous);
+                        Some((chunk.stream, start, end))
+                    }
+                    None => Some((chunk.stream, start, end)),
+                };
+            }
+        }
+        if let Some(last) = run {
+            flush(last);
+        }
+        Some(combined)
+    }
+
+    /// Bytes to persist for stderr; falls back to the text when no raw capture exists.
+    pub fn stderr_bytes(&self) -> &[u8] {
+        if self.stderr_raw.is_empty() {
+            self.stderr.as_bytes()
+        } else {
+            &self.stderr_raw
+        }
+    }
+}
+
+#[cfg(test)]
+impl CommandRecord {
+    /// A successful run of `command` in `/tmp`, for tests to adjust with struct-update syntax.
+    pub fn for_test(command: &str) -> Self {
+        CommandRecord {
+            command: command.to_string(),
+            command_hash: hash_command(command),
+            timestamp: Utc::now(),
+            working_dir: PathBuf::from("/tmp"),
+            exit_code: 0,
+            duration_ms: 1,
+            record_id: "r".to_string(),
+            short_code: None,
+            pty: false,
+            termination: TerminationReason::Completed,
+            signal: None,
+            signal_name: None,
+            core_dumped: false,
+            resources: None,
+            stdout_encoding: None,
+            stderr_encoding: None,
+            stdin_hash: None,
+            stdin_size: None,
+            env: None,
+            shell: None,
+            shell_argv: None,
+            raw_command: None,
+            stdout_truncation: None,
+            stderr_truncation: None,
+            git: None,
+            artifacts: None,
+            host: None,
+            stdout_sha256: None,
+            stderr_sha256: None,
+            stdout_codec: Compression::None,
+            stderr_codec: Compression::None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl CommandExecution {
+    /// A loaded run of `record` that printed `stdout`, for tests.
+    pub fn for_test(record: CommandRecord, stdout: &str) -> Self {
+        CommandExecution {
+            record,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+            stdout_path: None,
+            stderr_path: None,
+            streamed_stdout: false,
+            streamed_stderr: false,
+            stdout_raw: Vec::new(),
+            stderr_raw: Vec::new(),
+            stdout_view: None,
+            stderr_view: None,
+            stdin_path: None,
+            stdin_raw: None,
+            timeline: None,
+            stdout_staged: None,
+            stderr_staged: None,
+            artifacts_dir: None,
+            artifacts_staged: None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -111,6 +729,30 @@ mod tests {
         assert_eq!(format_command("ls  |  grep  a"), "ls|grep a");
     }
 
+    #[test]
+    fn truncation_maps_ranges_onto_head_and_tail() {
+        // 100 bytes produced; bytes 0..10 and 90..100 stored.
+        let truncation = OutputTruncation {
+            total_bytes: 100,
+            head_bytes: 10,
+            tail_bytes: 10,
+        };
+        assert_eq!(truncation.omitted_bytes(), 80);
+        assert_eq!(truncation.stored_ranges(0, 4), vec![(0, 4)]);
+        assert_eq!(truncation.stored_ranges(40, 10), vec![]);
+        assert_eq!(truncation.stored_ranges(8, 4), vec![(8, 2)]);
+        assert_eq!(truncation.stored_ranges(85, 10), vec![(10, 5)]);
+        assert_eq!(truncation.stored_ranges(5, 90), vec![(5, 5), (10, 5)]);
+        assert_eq!(
+            truncation.decode_with_marker(
+                b"head\n1234\nb\ntail",
+                |b| String::from_utf8_lossy(b).into_owned(),
+                "[cut]"
+            ),
+            "head\n1234\n[cut]\nb\ntail"
+        );
+    }
+
     #[test]
     fn test_hash_command_consistency() {
         let h1 = hash_command("ls -l");
@@ -119,4 +761,36 @@ mod tests {
         assert_eq!(h1, h2);
         assert_eq!(h1, h3);
     }
+
+    #[test]
+    fn read_view_keeps_head_and_tail_of_long_streams() {
+        let (bytes, view) = read_view(&b"0123456789"[..], 4).unwrap();
+        assert_eq!(bytes, b"0189");
+        assert_eq!(
+            view,
+            Some(OutputTruncation {
+                total_bytes: 10,
+                head_bytes: 2,
+                tail_bytes: 2,
+            })
+        );
+        let (bytes, view) = read_view(&b"0123"[..], 4).unwrap();
+        assert_eq!((bytes.as_slice(), view), (&b"0123"[..], None));
+    }
+
+    #[test]
+    fn record_keys_are_unique_and_ordered() {
+        let now = Utc::now();
+        let keys: Vec<String> = (0..100).map(|_| new_record_key(&now)).collect();
+        let nanos: Vec<i64> = keys.iter().map(|k| record_key_nanos(k).unwrap()).collect();
+        assert!(nanos.windows(2).all(|w| w[0] < w[1]));
+        assert!(nanos[0] >= now.timestamp_nanos_opt().unwrap());
+        let pid = format!("{:x}", std::process::id());
+        assert!(keys.iter().all(|k| k.split_once('-').unwrap().1 == pid));
+        assert_eq!(
+            record_key_nanos("1700000000"),
+            Some(1_700_000_000_000_000_000)
+        );
+        assert_eq!(record_key_nanos("meta"), None);
+    }
 }
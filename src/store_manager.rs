@@ -1,13 +1,31 @@
-use crate::storage::{CommandExecution, CommandRecord};
+use crate::storage::{CommandExecution, CommandRecord, Compression, OutputTruncation};
 use anyhow::{Context, Result};
 use chrono::{Datelike, Duration, Utc};
 use std::collections::HashSet;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
+/// Where the contents of a blob come from.
+#[derive(Clone, Copy)]
+enum BlobSource<'a> {
+    File(&'a Path),
+    Bytes(&'a [u8]),
+}
+
+impl BlobSource<'_> {
+    fn open(&self) -> std::io::Result<Box<dyn std::io::Read + '_>> {
+        Ok(match self {
+            BlobSource::File(path) => Box::new(std::io::BufReader::new(fs::File::open(path)?)),
+            BlobSource::Bytes(bytes) => Box::new(*bytes),
+        })
+    }
+}
+
 pub struct StoreManager {
     base_dir: PathBuf,
     config: crate::config::Config,
+    encoding_override: Option<&'static encoding_rs::Encoding>,
 }
 
 impl StoreManager {
@@ -29,16 +47,33 @@ impl StoreManager {
         let records_dir = base_dir.join("records");
         fs::create_dir_all(&records_dir).context(i18n.t("error_create_records_dir"))?;
 
-        Ok(Self { base_dir, config })
+        Ok(Self {
+            base_dir,
+            config,
+            encoding_override: None,
+        })
+    }
+
+    /// Decode stored output with this encoding instead of the recorded/detected one.
+    pub fn set_encoding_override(&mut self, encoding: Option<&'static encoding_rs::Encoding>) {
+        self.encoding_override = encoding;
+    }
+
+    /// Root of the data directory.
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
     }
 
     // Removed unused convenience constructor to avoid dead_code warnings.
 
+    /// Save a run and add it to the index. Another process may have taken the
+    /// run's short code since it was assigned; it then gets the next free one.
     pub fn save_execution(
         &self,
-        execution: &CommandExecution,
+        execution: &mut CommandExecution,
         i18n: &crate::i18n::I18n,
     ) -> Result<()> {
+        let _lock = self.lock()?;
         let record_dir = self
             .base_dir
             .join("records")
@@ -46,31 +81,265 @@ impl StoreManager {
 
         fs::create_dir_all(&record_dir).context(i18n.t("error_create_record_dir"))?;
 
-        let meta_path = record_dir.join(format!(
-            "meta_{}.json",
-            execution.record.timestamp.timestamp()
-        ));
-        let stdout_path = record_dir.join(format!(
-            "stdout_{}.txt",
-            execution.record.timestamp.timestamp()
-        ));
-        let stderr_path = record_dir.join(format!(
-            "stderr_{}.txt",
-            execution.record.timestamp.timestamp()
-        ));
+        let used = self.used_short_codes(&execution.record.command_hash)?;
+        if execution
+            .record
+            .short_code
+            .as_ref()
+            .is_none_or(|code| used.contains(code))
+        {
+            execution.record.short_code = Some(Self::first_free_short_code(&used));
+        }
 
-        serde_json::to_writer_pretty(fs::File::create(&meta_path)?, &execution.record)
-            .context(i18n.t("error_save_metadata"))?;
+        // A key can only repeat if an earlier dt with this pid recorded the same
+        // nanosecond, e.g. after the clock was set back; take a fresh one then.
+        let mut key = execution.record.file_key();
+        while record_dir.join(format!("meta_{}.json", key)).exists() {
+            key = crate::storage::new_record_key(&execution.record.timestamp);
+            execution.record.record_id = format!("{}_{}", execution.record.command_hash, key);
+        }
+        let meta_path = record_dir.join(format!("meta_{}.json", key));
+
+        // Outputs go in first so the meta file never names a missing blob.
+        let mut record = execution.record.clone();
+        let source = match &execution.stdout_staged {
+            Some(staged) => BlobSource::File(staged),
+            None => BlobSource::Bytes(execution.stdout_bytes()),
+        };
+        let (hash, codec) = self
+            .store_blob(source)
+            .context(i18n.t("error_save_stdout"))?;
+        (record.stdout_sha256, record.stdout_codec) = (Some(hash), codec);
+        let source = match &execution.stderr_staged {
+            Some(staged) => BlobSource::File(staged),
+            None => BlobSource::Bytes(execution.stderr_bytes()),
+        };
+        let (hash, codec) = self
+            .store_blob(source)
+            .context(i18n.t("error_save_stderr"))?;
+        (record.stderr_sha256, record.stderr_codec) = (Some(hash), codec);
+
+        // Never replace another run's metadata; the store lock keeps the key free.
+        write_json_atomic(&meta_path, &record, false).context(i18n.t("error_save_metadata"))?;
+
+        if let Some(stdin) = &execution.stdin_raw {
+            let stdin_path = record_dir.join(format!("stdin_{}.bin", key));
+            write_atomic(&stdin_path, stdin, false).context(i18n.t("error_save_stdin"))?;
+        }
 
-        fs::write(&stdout_path, &execution.stdout).context(i18n.t("error_save_stdout"))?;
+        if let Some(timeline) = &execution.timeline {
+            let timeline_path = record_dir.join(format!("timeline_{}.json", key));
+            serde_json::to_vec(timeline)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| write_atomic(&timeline_path, &json, false))
+                .context(i18n.t("error_save_timeline"))?;
+        }
 
-        fs::write(&stderr_path, &execution.stderr).context(i18n.t("error_save_stderr"))?;
+        if let (Some(tracked), Some(snapshot)) =
+            (&execution.record.artifacts, &execution.artifacts_dir)
+        {
+            let artifacts_dir = record_dir.join(format!("artifacts_{}", key));
+            fs::create_dir_all(&artifacts_dir).context(i18n.t("error_save_artifacts"))?;
+            for index in 0..tracked.files.len() {
+                let name = index.to_string();
+                let dest = artifacts_dir.join(&name);
+                let _ = fs::remove_file(&dest);
+                fs::hard_link(snapshot.join(&name), &dest)
+                    .or_else(|_| fs::copy(snapshot.join(&name), &dest).map(|_| ()))
+                    .context(i18n.t("error_save_artifacts"))?;
+            }
+        }
 
-        self.update_index(&execution.record, i18n)?;
+        self.update_index(&record, i18n)?;
 
         Ok(())
     }
 
+    /// Shared store of output contents, one file per distinct SHA-256.
+    fn blobs_dir(&self) -> PathBuf {
+        self.base_dir.join("blobs")
+    }
+
+    /// Location of the blob with this hash and codec:
+    /// `blobs/<first two hex digits>/<hash>[.gz|.zst]`.
+    pub fn blob_path(&self, hash: &str, codec: Compression) -> PathBuf {
+        self.blobs_dir()
+            .join(&hash[..hash.len().min(2)])
+            .join(format!("{}{}", hash, codec.extension()))
+    }
+
+    /// Store the contents of `source` as a blob unless an identical one exists, and
+    /// return its hash and how it is compressed. Contents are streamed, never held
+    /// in memory whole. Outputs of at least `storage.compression_threshold` bytes use
+    /// `storage.compression` when that makes them smaller. An uncompressed blob is
+    /// linked from a source file (output streamed to the staging area, or a legacy
+    /// output file) instead of being rewritten.
+    fn store_blob(&self, source: BlobSource) -> std::io::Result<(String, Compression)> {
+        let (hash, len) = match source {
+            BlobSource::File(path) => (crate::storage::hash_file(path)?, fs::metadata(path)?.len()),
+            BlobSource::Bytes(bytes) => (crate::storage::hash_bytes(bytes), bytes.len() as u64),
+        };
+        if let Some(codec) = Compression::ALL
+            .into_iter()
+            .find(|&codec| self.blob_path(&hash, codec).exists())
+        {
+            return Ok((hash, codec));
+        }
+        let dir = self.blobs_dir().join(&hash[..2]);
+        fs::create_dir_all(&dir)?;
+
+        let storage = &self.config.storage;
+        if storage.compression != Compression::None && len >= storage.compression_threshold {
+            let codec = storage.compression;
+            let file = crate::compression::compress_stream(
+                codec,
+                source.open()?,
+                tempfile::NamedTempFile::new_in(&dir)?,
+            )?;
+            if file.as_file().metadata()?.len() < len {
+                Self::persist_blob(file, &self.blob_path(&hash, codec))?;
+                return Ok((hash, codec));
+            }
+        }
+
+        let path = self.blob_path(&hash, Compression::None);
+        if let BlobSource::File(existing) = source {
+            if fs::hard_link(existing, &path).is_ok() || path.exists() {
+                return Ok((hash, Compression::None));
+            }
+        }
+        let mut file = tempfile::NamedTempFile::new_in(&dir)?;
+        std::io::copy(&mut source.open()?, &mut file)?;
+        Self::persist_blob(file, &path)?;
+        Ok((hash, Compression::None))
+    }
+
+    /// Move a blob written under a temporary name (so a partial blob is never
+    /// visible) into place. Another process storing the same blob first is fine.
+    fn persist_blob(file: tempfile::NamedTempFile, path: &Path) -> std::io::Result<()> {
+        match file.persist_noclobber(path) {
+            Ok(_) => Ok(()),
+            Err(_) if path.exists() => Ok(()),
+            Err(e) => Err(e.error),
+        }
+    }
+
+    /// Stored output of one stream: its blob, or `<stream>_<ts>.txt` in the record
+    /// directory for records saved before outputs were deduplicated.
+    fn output_path(
+        &self,
+        hash: Option<&str>,
+        codec: Compression,
+        record_dir: &Path,
+        stream: &str,
+        key: &str,
+    ) -> PathBuf {
+        match hash {
+            Some(hash) => self.blob_path(hash, codec),
+            None => record_dir.join(format!("{}_{}.txt", stream, key)),
+        }
+    }
+
+    /// Read the in-memory view of stored output (see `MAX_VIEW_BYTES`),
+    /// decompressing it when its blob is compressed.
+    fn read_output(path: &Path, codec: Compression) -> Option<(Vec<u8>, Option<OutputTruncation>)> {
+        let file = std::io::BufReader::new(fs::File::open(path).ok()?);
+        let reader = crate::compression::decompress_stream(codec, file).ok()?;
+        crate::storage::read_view(reader, crate::storage::MAX_VIEW_BYTES).ok()
+    }
+
+    /// Move a record's legacy `stdout_<ts>.txt`/`stderr_<ts>.txt` into the blob
+    /// store and reference them by hash. Returns whether the record changed.
+    fn migrate_outputs(&self, record: &mut CommandRecord, record_dir: &Path) -> Result<bool> {
+        let key = record.file_key();
+        let mut changed = false;
+        for (stream, hash, codec) in [
+            (
+                "stdout",
+                &mut record.stdout_sha256,
+                &mut record.stdout_codec,
+            ),
+            (
+                "stderr",
+                &mut record.stderr_sha256,
+                &mut record.stderr_codec,
+            ),
+        ] {
+            let legacy = record_dir.join(format!("{}_{}.txt", stream, key));
+            if hash.is_some() || !legacy.exists() {
+                continue;
+            }
+            let (stored, stored_codec) = self.store_blob(BlobSource::File(&legacy))?;
+            (*hash, *codec) = (Some(stored), stored_codec);
+            fs::remove_file(&legacy)?;
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    /// Delete blobs that no record refers to any more.
+    fn remove_unreferenced_blobs(&self, referenced: &HashSet<String>) -> Result<()> {
+        let blobs_dir = self.blobs_dir();
+        if !blobs_dir.exists() {
+            return Ok(());
+        }
+        for prefix in fs::read_dir(&blobs_dir)? {
+            let prefix = prefix?.path();
+            if !prefix.is_dir() {
+                continue;
+            }
+            for blob in fs::read_dir(&prefix)? {
+                let blob = blob?.path();
+                let name = blob.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                let hash = name.split('.').next().unwrap_or(name);
+                // Dot files are blobs still being written by a concurrent save.
+                if !name.starts_with('.') && !referenced.contains(hash) {
+                    let _ = fs::remove_file(&blob);
+                }
+            }
+            // Only succeeds once the prefix directory is empty.
+            let _ = fs::remove_dir(&prefix);
+        }
+        Ok(())
+    }
+
+    /// Where running commands stream their output before it is saved.
+    pub fn staging_dir(&self) -> PathBuf {
+        self.base_dir.join("staging")
+    }
+
+    /// Delete output and snapshots left in the staging area by runs that crashed
+    /// before saving. A running command holds a lock on its output files, and
+    /// anything touched in the last day is kept in case it is about to be saved.
+    fn remove_stale_staging(&self) -> Result<()> {
+        let staging = self.staging_dir();
+        if !staging.exists() {
+            return Ok(());
+        }
+        let cutoff = std::time::SystemTime::now() - std::time::Duration::from_secs(24 * 60 * 60);
+        for entry in fs::read_dir(&staging)? {
+            let entry = entry?;
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let stale = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| modified < cutoff);
+            if !stale {
+                continue;
+            }
+            if name.ends_with(".part") {
+                let unlocked = fs::File::open(&path).is_ok_and(|file| file.try_lock().is_ok());
+                if unlocked {
+                    let _ = fs::remove_file(&path);
+                }
+            } else if name.starts_with("artifacts_") && path.is_dir() {
+                let _ = fs::remove_dir_all(&path);
+            }
+        }
+        Ok(())
+    }
+
     /// Assign a minimal unused short code for the given record (per command hash).
     /// Codes are bijective base62 with alphabet a-zA-Z0-9, starting from 1 => 'a'.
     pub fn assign_short_code(
@@ -78,7 +347,15 @@ impl StoreManager {
         record: &mut CommandRecord,
         _i18n: &crate::i18n::I18n,
     ) -> Result<()> {
-        let record_dir = self.base_dir.join("records").join(&record.command_hash);
+        let _lock = self.lock()?;
+        let used = self.used_short_codes(&record.command_hash)?;
+        record.short_code = Some(Self::first_free_short_code(&used));
+        Ok(())
+    }
+
+    /// Short codes of the saved runs of a command.
+    fn used_short_codes(&self, command_hash: &str) -> Result<HashSet<String>> {
+        let record_dir = self.base_dir.join("records").join(command_hash);
 
         let mut used: HashSet<String> = HashSet::new();
 
@@ -105,19 +382,15 @@ impl StoreManager {
                 }
             }
         }
+        Ok(used)
+    }
 
-        // Find minimal unused n starting from 1
-        let mut n: u64 = 1;
-        loop {
-            let code = Self::encode_bijective_base62(n);
-            if !used.contains(&code) {
-                record.short_code = Some(code);
-                break;
-            }
-            n += 1;
-        }
-
-        Ok(())
+    /// Find minimal unused code starting from 1
+    fn first_free_short_code(used: &HashSet<String>) -> String {
+        (1..)
+            .map(Self::encode_bijective_base62)
+            .find(|code| !used.contains(code))
+            .unwrap()
     }
 
     fn encode_bijective_base62(mut n: u64) -> String {
@@ -170,13 +443,13 @@ impl StoreManager {
             }
         }
 
-        executions.sort_by(|a, b| a.record.timestamp.cmp(&b.record.timestamp));
+        executions.sort_by_key(|e| e.record.timestamp);
         Ok(executions)
     }
 
-    /// Get sorted execution records by timestamp (ascending) for a given command hash.
-    /// Returns a list of (timestamp, meta_file_path) tuples.
-    pub fn get_sorted_records(&self, command_hash: &str) -> Result<Vec<(u64, PathBuf)>> {
+    /// Get sorted execution records by record key (ascending) for a given command hash.
+    /// Returns a list of (record_key, meta_file_path) tuples.
+    pub fn get_sorted_records(&self, command_hash: &str) -> Result<Vec<(String, PathBuf)>> {
         let record_dir = self.base_dir.join("records").join(command_hash);
         if !record_dir.exists() {
             return Ok(Vec::new());
@@ -202,35 +475,37 @@ impl StoreManager {
                 continue;
             }
 
-            // Extract timestamp from filename (meta_<timestamp>.json)
-            if let Some(ts_str) = filename
+            // Extract the record key from filename (meta_<key>.json)
+            if let Some(key) = filename
                 .strip_prefix("meta_")
                 .and_then(|s| s.strip_suffix(".json"))
             {
-                if let Ok(ts) = ts_str.parse::<u64>() {
-                    entries.push((ts, path));
+                if crate::storage::record_key_nanos(key).is_some() {
+                    entries.push((key.to_string(), path));
                 }
             }
         }
 
-        entries.sort_by_key(|(ts, _)| *ts);
+        entries.sort_by(|(a, _), (b, _)| {
+            (crate::storage::record_key_nanos(a), a).cmp(&(crate::storage::record_key_nanos(b), b))
+        });
         Ok(entries)
     }
 
-    /// Get a specific target execution (first or last) for a command, excluding a given timestamp.
+    /// Get a specific target execution (first or last) for a command, excluding a given record.
     /// This is used for auto-diff after running a command.
     pub fn get_target_record(
         &self,
         command_hash: &str,
         target: crate::DiffTarget,
-        exclude_timestamp: u64,
+        exclude_record_id: &str,
     ) -> Result<Option<CommandExecution>> {
         let records = self.get_sorted_records(command_hash)?;
 
         // Filter out the current execution
-        let candidates: Vec<(u64, PathBuf)> = records
+        let candidates: Vec<(String, PathBuf)> = records
             .into_iter()
-            .filter(|(ts, _)| *ts != exclude_timestamp)
+            .filter(|(key, _)| format!("{}_{}", command_hash, key) != exclude_record_id)
             .collect();
 
         // Select first or last based on target
@@ -251,6 +526,21 @@ impl StoreManager {
         }
     }
 
+    /// Load one indexed run with its outputs, without touching the command's other runs.
+    pub fn load_execution(
+        &self,
+        record: &CommandRecord,
+        i18n: &crate::i18n::I18n,
+    ) -> Result<CommandExecution> {
+        let meta_path = self
+            .base_dir
+            .join("records")
+            .join(&record.command_hash)
+            .join(format!("meta_{}.json", record.file_key()));
+        self.load_execution_from_meta(&meta_path, i18n)
+            .with_context(|| format!("Failed to load execution from {}", meta_path.display()))
+    }
+
     fn load_execution_from_meta(
         &self,
         meta_path: &Path,
@@ -258,17 +548,62 @@ impl StoreManager {
     ) -> Result<CommandExecution> {
         let record: CommandRecord = serde_json::from_reader(fs::File::open(meta_path)?)?;
 
-        let timestamp = record.timestamp.timestamp();
+        let key = record.file_key();
         let record_dir = meta_path.parent().unwrap();
 
-        let stdout_path = record_dir.join(format!("stdout_{}.txt", timestamp));
-        let stderr_path = record_dir.join(format!("stderr_{}.txt", timestamp));
-
-        let stdout =
-            fs::read_to_string(&stdout_path).unwrap_or_else(|_| i18n.t("error_read_stdout"));
+        let stdout_path = self.output_path(
+            record.stdout_sha256.as_deref(),
+            record.stdout_codec,
+            record_dir,
+            "stdout",
+            &key,
+        );
+        let stderr_path = self.output_path(
+            record.stderr_sha256.as_deref(),
+            record.stderr_codec,
+            record_dir,
+            "stderr",
+            &key,
+        );
+        let stdin_path = record_dir.join(format!("stdin_{}.bin", key));
+        let timeline_path = record_dir.join(format!("timeline_{}.json", key));
+        let artifacts_dir = record_dir.join(format!("artifacts_{}", key));
+
+        let stdout_raw = Self::read_output(&stdout_path, record.stdout_codec);
+        let stdout = match &stdout_raw {
+            Some((bytes, view)) => crate::storage::decode_output(
+                bytes,
+                view.as_ref(),
+                record.stdout_truncation.as_ref(),
+                |bytes| {
+                    crate::encoding::decode_stored(
+                        bytes,
+                        record.stdout_encoding.as_deref(),
+                        self.encoding_override,
+                    )
+                },
+                i18n,
+            ),
+            None => i18n.t("error_read_stdout"),
+        };
 
-        let stderr =
-            fs::read_to_string(&stderr_path).unwrap_or_else(|_| i18n.t("error_read_stderr"));
+        let stderr_raw = Self::read_output(&stderr_path, record.stderr_codec);
+        let stderr = match &stderr_raw {
+            Some((bytes, view)) => crate::storage::decode_output(
+                bytes,
+                view.as_ref(),
+                record.stderr_truncation.as_ref(),
+                |bytes| {
+                    crate::encoding::decode_stored(
+                        bytes,
+                        record.stderr_encoding.as_deref(),
+                        self.encoding_override,
+                    )
+                },
+                i18n,
+            ),
+            None => i18n.t("error_read_stderr"),
+        };
 
         Ok(CommandExecution {
             record,
@@ -278,18 +613,72 @@ impl StoreManager {
             stderr_path: Some(stderr_path),
             streamed_stdout: false,
             streamed_stderr: false,
+            stdout_view: stdout_raw.as_ref().and_then(|(_, view)| *view),
+            stderr_view: stderr_raw.as_ref().and_then(|(_, view)| *view),
+            stdout_raw: stdout_raw.map(|(bytes, _)| bytes).unwrap_or_default(),
+            stderr_raw: stderr_raw.map(|(bytes, _)| bytes).unwrap_or_default(),
+            stdin_path: stdin_path.exists().then_some(stdin_path),
+            stdin_raw: None,
+            timeline: fs::File::open(&timeline_path)
+                .ok()
+                .and_then(|file| serde_json::from_reader(std::io::BufReader::new(file)).ok()),
+            stdout_staged: None,
+            stderr_staged: None,
+            artifacts_dir: artifacts_dir.is_dir().then_some(artifacts_dir),
+            artifacts_staged: None,
         })
     }
 
-    fn update_index(&self, record: &CommandRecord, i18n: &crate::i18n::I18n) -> Result<()> {
+    /// Hold an exclusive advisory lock on `~/.dt/lock` while records, blobs or
+    /// the index change, so concurrent dt processes do not lose each other's
+    /// writes. Released when the returned file is dropped.
+    fn lock(&self) -> Result<fs::File> {
+        let path = self.base_dir.join("lock");
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+        file.lock()
+            .with_context(|| format!("Failed to lock {}", path.display()))?;
+        Ok(file)
+    }
+
+    fn read_index(&self) -> Result<Vec<CommandRecord>> {
         let index_path = self.base_dir.join("index");
 
-        let mut entries = Vec::new();
-        if index_path.exists() {
-            if let Ok(content) = fs::read_to_string(&index_path) {
-                entries = serde_json::from_str(&content).unwrap_or_else(|_| Vec::new());
+        if !index_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let records: Vec<CommandRecord> =
+            serde_json::from_reader(std::io::BufReader::new(fs::File::open(&index_path)?))?;
+        Ok(records)
+    }
+
+    /// Read the index, rebuilding it from the record files when it cannot be
+    /// parsed (e.g. left truncated by an older dt that crashed mid-write).
+    /// Callers hold the store lock.
+    fn load_index(&self, i18n: &crate::i18n::I18n) -> Result<Vec<CommandRecord>> {
+        match self.read_index() {
+            Ok(records) => Ok(records),
+            Err(_) => {
+                eprintln!("{}", i18n.t("warn_index_rebuilt"));
+                self.rebuild_index(i18n)?;
+                self.read_index()
             }
         }
+    }
+
+    fn update_index(&self, record: &CommandRecord, i18n: &crate::i18n::I18n) -> Result<()> {
+        let index_path = self.base_dir.join("index");
+
+        let Ok(mut entries) = self.read_index() else {
+            // The rebuilt index already includes this record's meta file.
+            self.load_index(i18n)?;
+            return Ok(());
+        };
 
         // Check if archiving is needed
         if self.config.storage.auto_archive {
@@ -303,10 +692,12 @@ impl StoreManager {
             Utc::now() - Duration::days(self.config.storage.max_retention_days as i64);
         entries.retain(|r| r.timestamp > cutoff_date);
 
-        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        entries.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+        // The environment is only kept in the meta files, so it is not copied into
+        // every index entry; this also drops it from entries of older versions.
+        entries.iter_mut().for_each(|r| r.env = None);
 
-        serde_json::to_writer_pretty(fs::File::create(index_path)?, &entries)
-            .context(i18n.t("error_update_index"))?;
+        write_json_atomic(&index_path, &entries, true).context(i18n.t("error_update_index"))?;
 
         Ok(())
     }
@@ -348,9 +739,9 @@ impl StoreManager {
                 }
 
                 existing_records.extend(records);
-                existing_records.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+                existing_records.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
 
-                serde_json::to_writer_pretty(fs::File::create(archive_path)?, &existing_records)
+                write_json_atomic(&archive_path, &existing_records, true)
                     .context(i18n.t_format("error_save_archive", &[&year.to_string()]))?;
             }
 
@@ -362,18 +753,18 @@ impl StoreManager {
     }
 
     pub fn get_all_records(&self) -> Result<Vec<CommandRecord>> {
-        let index_path = self.base_dir.join("index");
-
-        if !index_path.exists() {
-            return Ok(Vec::new());
+        if let Ok(records) = self.read_index() {
+            return Ok(records);
         }
-
-        let records: Vec<CommandRecord> = serde_json::from_reader(fs::File::open(&index_path)?)?;
-        Ok(records)
+        let _lock = self.lock()?;
+        let lang = self.config.get_effective_language();
+        let i18n = crate::i18n::I18n::new(&lang);
+        self.load_index(&i18n)
     }
 
     pub fn clean_by_query(&self, query: &str, i18n: &crate::i18n::I18n) -> Result<usize> {
-        let records = self.get_all_records()?;
+        let _lock = self.lock()?;
+        let records = self.load_index(i18n)?;
         let mut cleaned = 0;
 
         let q = query.trim();
@@ -415,7 +806,8 @@ impl StoreManager {
     }
 
     pub fn clean_by_file(&self, file_path: &Path, i18n: &crate::i18n::I18n) -> Result<usize> {
-        let records = self.get_all_records()?;
+        let _lock = self.lock()?;
+        let records = self.load_index(i18n)?;
         let mut cleaned = 0;
 
         // Try to get absolute path, use original path if failed
@@ -640,12 +1032,18 @@ impl StoreManager {
     }
 
     pub fn clean_all(&self, _i18n: &crate::i18n::I18n) -> Result<usize> {
+        let _lock = self.lock()?;
         let records_dir = self.base_dir.join("records");
         if records_dir.exists() {
             fs::remove_dir_all(&records_dir)?;
             fs::create_dir_all(&records_dir)?;
         }
 
+        let blobs_dir = self.blobs_dir();
+        if blobs_dir.exists() {
+            fs::remove_dir_all(&blobs_dir)?;
+        }
+
         let index_path = self.base_dir.join("index");
         if index_path.exists() {
             fs::remove_file(&index_path)?;
@@ -654,17 +1052,24 @@ impl StoreManager {
         Ok(0)
     }
 
+    /// Remove a record's files. Its output blobs may be shared with other runs;
+    /// unreferenced ones are dropped when the index is rebuilt.
     fn clean_record(&self, record: &CommandRecord) -> Result<()> {
         let record_dir = self.base_dir.join("records").join(&record.command_hash);
 
-        let timestamp = record.timestamp.timestamp();
-        let meta_path = record_dir.join(format!("meta_{}.json", timestamp));
-        let stdout_path = record_dir.join(format!("stdout_{}.txt", timestamp));
-        let stderr_path = record_dir.join(format!("stderr_{}.txt", timestamp));
+        let key = record.file_key();
+        let meta_path = record_dir.join(format!("meta_{}.json", key));
+        let stdout_path = record_dir.join(format!("stdout_{}.txt", key));
+        let stderr_path = record_dir.join(format!("stderr_{}.txt", key));
+        let stdin_path = record_dir.join(format!("stdin_{}.bin", key));
+        let timeline_path = record_dir.join(format!("timeline_{}.json", key));
 
         let _ = fs::remove_file(meta_path);
         let _ = fs::remove_file(stdout_path);
         let _ = fs::remove_file(stderr_path);
+        let _ = fs::remove_file(stdin_path);
+        let _ = fs::remove_file(timeline_path);
+        let _ = fs::remove_dir_all(record_dir.join(format!("artifacts_{}", key)));
 
         Ok(())
     }
@@ -674,6 +1079,7 @@ impl StoreManager {
         execution: &CommandExecution,
         i18n: &crate::i18n::I18n,
     ) -> Result<()> {
+        let _lock = self.lock()?;
         self.clean_record(&execution.record)?;
         self.rebuild_index(i18n)?;
         Ok(())
@@ -681,6 +1087,7 @@ impl StoreManager {
 
     /// Public method to rebuild index (default: last year's records)
     pub fn rebuild_index_public(&self, i18n: &crate::i18n::I18n) -> Result<()> {
+        let _lock = self.lock()?;
         self.rebuild_index_since(Some(Utc::now() - Duration::days(365)), i18n)
     }
 
@@ -689,7 +1096,9 @@ impl StoreManager {
         self.rebuild_index_since(None, i18n)
     }
 
-    /// Rebuild index with optional time filter
+    /// Rebuild index with optional time filter. Every record is migrated to the
+    /// current layout (command normalization, output blobs) and blobs no record
+    /// refers to are deleted, whatever the filter.
     fn rebuild_index_since(
         &self,
         since: Option<chrono::DateTime<Utc>>,
@@ -697,6 +1106,10 @@ impl StoreManager {
     ) -> Result<()> {
         let records_dir = self.base_dir.join("records");
         let mut all_records = Vec::new();
+        let mut referenced: HashSet<String> = HashSet::new();
+        // Blobs an unreadable record may refer to cannot be told apart, so
+        // unreferenced blobs are only deleted when every record was read.
+        let mut skipped = false;
 
         if records_dir.exists() {
             // First pass: collect all meta files to avoid modification during iteration
@@ -721,60 +1134,84 @@ impl StoreManager {
 
             // Second pass: process, potentially migrate, and collect for index
             for meta_path in meta_files {
-                let mut record: CommandRecord =
-                    serde_json::from_reader(fs::File::open(&meta_path)?)?;
+                let read = fs::File::open(&meta_path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|file| Ok(serde_json::from_reader(std::io::BufReader::new(file))?));
+                let mut record: CommandRecord = match read {
+                    Ok(record) => record,
+                    Err(error) => {
+                        eprintln!(
+                            "{}",
+                            i18n.t_format(
+                                "warn_skipped_record",
+                                &[&meta_path.display().to_string(), &error.to_string()]
+                            )
+                        );
+                        skipped = true;
+                        continue;
+                    }
+                };
 
                 let new_command = crate::storage::format_command(&record.command);
                 let new_hash = crate::storage::hash_command(&new_command);
+                let old_key = meta_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.strip_prefix("meta_"))
+                    .and_then(|n| n.strip_suffix(".json"))
+                    .map(str::to_string)
+                    .unwrap_or_else(|| record.file_key());
+                // Records saved before sub-second ids are keyed by Unix seconds.
+                let new_key = if old_key.contains('-') {
+                    old_key.clone()
+                } else {
+                    crate::storage::new_record_key(&record.timestamp)
+                };
 
-                if new_hash != record.command_hash || new_command != record.command {
-                    let old_hash = record.command_hash.clone();
-                    let timestamp = record.timestamp.timestamp();
-
-                    record.command = new_command;
-                    record.command_hash = new_hash.clone();
-                    record.record_id =
-                        format!("{}_{}", record.command_hash, record.timestamp.timestamp());
-
-                    let old_dir = records_dir.join(&old_hash);
+                if new_hash != record.command_hash
+                    || new_command != record.command
+                    || new_key != old_key
+                {
+                    let old_dir = records_dir.join(&record.command_hash);
                     let new_dir = records_dir.join(&new_hash);
                     fs::create_dir_all(&new_dir)?;
 
-                    if let Some(filename) = meta_path.file_name() {
-                        let new_meta_path = new_dir.join(filename);
-                        let old_stdout = old_dir.join(format!("stdout_{}.txt", timestamp));
-                        let old_stderr = old_dir.join(format!("stderr_{}.txt", timestamp));
-                        let new_stdout = new_dir.join(format!("stdout_{}.txt", timestamp));
-                        let new_stderr = new_dir.join(format!("stderr_{}.txt", timestamp));
-
-                        if old_stdout != new_stdout && old_stdout.exists() {
-                            fs::rename(&old_stdout, &new_stdout).or_else(
-                                |_| -> std::io::Result<()> {
-                                    fs::copy(&old_stdout, &new_stdout)?;
-                                    fs::remove_file(&old_stdout).ok();
-                                    Ok(())
-                                },
-                            )?;
-                        }
-                        if old_stderr != new_stderr && old_stderr.exists() {
-                            fs::rename(&old_stderr, &new_stderr).or_else(
-                                |_| -> std::io::Result<()> {
-                                    fs::copy(&old_stderr, &new_stderr)?;
-                                    fs::remove_file(&old_stderr).ok();
-                                    Ok(())
-                                },
-                            )?;
+                    record.command = new_command;
+                    record.command_hash = new_hash;
+                    record.record_id = format!("{}_{}", record.command_hash, new_key);
+
+                    for (prefix, suffix) in [
+                        ("stdout_", ".txt"),
+                        ("stderr_", ".txt"),
+                        ("stdin_", ".bin"),
+                        ("timeline_", ".json"),
+                        ("artifacts_", ""),
+                    ] {
+                        let old_path = old_dir.join(format!("{}{}{}", prefix, old_key, suffix));
+                        let new_path = new_dir.join(format!("{}{}{}", prefix, new_key, suffix));
+                        if old_path != new_path && old_path.exists() {
+                            move_path(&old_path, &new_path)?;
                         }
+                    }
 
-                        serde_json::to_writer_pretty(fs::File::create(&new_meta_path)?, &record)?;
+                    let new_meta_path = new_dir.join(format!("meta_{}.json", new_key));
+                    write_json_atomic(&new_meta_path, &record, true)?;
 
-                        if meta_path != new_meta_path {
-                            fs::remove_file(&meta_path).ok();
-                        }
+                    if meta_path != new_meta_path {
+                        fs::remove_file(&meta_path).ok();
                     }
                 }
 
-                // Add to index if it matches time filter
+                let record_dir = records_dir.join(&record.command_hash);
+                if self.migrate_outputs(&mut record, &record_dir)? {
+                    let meta_path = record_dir.join(format!("meta_{}.json", record.file_key()));
+                    write_json_atomic(&meta_path, &record, true)?;
+                }
+                referenced.extend(record.stdout_sha256.iter().cloned());
+                referenced.extend(record.stderr_sha256.iter().cloned());
+
+                // Add to index if it matches time filter; the environment stays in the meta file
+                record.env = None;
                 if let Some(cutoff) = since {
                     if record.timestamp >= cutoff {
                         all_records.push(record);
@@ -798,16 +1235,69 @@ impl StoreManager {
             }
         }
 
-        all_records.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        all_records.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
 
         let index_path = self.base_dir.join("index");
-        serde_json::to_writer_pretty(fs::File::create(index_path)?, &all_records)
+        write_json_atomic(&index_path, &all_records, true)
             .context(i18n.t("error_rebuild_index"))?;
 
+        if !skipped {
+            self.remove_unreferenced_blobs(&referenced)?;
+        }
+        self.remove_stale_staging()?;
+
         Ok(())
     }
 }
 
+/// Rename `from` to `to`, copying (recursively, for the `artifacts_<key>`
+/// directories) and deleting the original when a rename is not possible.
+fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_path(from, to)?;
+    if from.is_dir() {
+        fs::remove_dir_all(from).ok();
+    } else {
+        fs::remove_file(from).ok();
+    }
+    Ok(())
+}
+
+fn copy_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !fs::symlink_metadata(from)?.is_dir() {
+        return fs::copy(from, to).map(|_| ());
+    }
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        copy_path(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// Write `bytes` to a temporary file beside `path`, flush it to disk and rename
+/// it into place, so a crash never leaves a partial file behind. Unless
+/// `replace` is set, an existing file at `path` is an error.
+fn write_atomic(path: &Path, bytes: &[u8], replace: bool) -> Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(bytes)?;
+    file.as_file().sync_all()?;
+    if replace {
+        file.persist(path)?;
+    } else {
+        file.persist_noclobber(path)?;
+    }
+    Ok(())
+}
+
+/// `write_atomic` for `value` as pretty-printed JSON.
+fn write_json_atomic<T: serde::Serialize>(path: &Path, value: &T, replace: bool) -> Result<()> {
+    write_atomic(path, &serde_json::to_vec_pretty(value)?, replace)
+}
+
 #[cfg(test)]
 #[allow(dead_code)]
 mod test_support {
@@ -822,7 +1312,8 @@ mod test_support {
         }
 
         pub fn backup_by_file(&self, file_path: &Path, i18n: &crate::i18n::I18n) -> Result<usize> {
-            let records = self.get_all_records()?;
+            let _lock = self.lock()?;
+            let records = self.load_index(i18n)?;
 
             let target_path = match fs::canonicalize(file_path) {
                 Ok(abs_path) => abs_path,
@@ -880,8 +1371,8 @@ mod test_support {
                 }
             }
 
-            existing.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-            serde_json::to_writer_pretty(fs::File::create(&archive_path)?, &existing)
+            existing.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+            write_json_atomic(&archive_path, &existing, true)
                 .context(i18n.t_format("error_save_archive", &[&year.to_string()]))?;
 
             Ok(existing.len())
@@ -922,6 +1413,360 @@ mod tests {
         assert!(files.contains(&std::path::PathBuf::from(out)));
     }
 
+    #[test]
+    fn outputs_are_stored_once_migrated_and_collected() {
+        use crate::executor::{CommandExecutor, ExecOptions};
+        let i18n = crate::i18n::I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = StoreManager::new_with_config_and_base_dir(
+            crate::config::Config::default(),
+            &i18n,
+            Some(dir.path().to_path_buf()),
+        )
+        .unwrap();
+        let options = ExecOptions {
+            quiet: true,
+            ..Default::default()
+        };
+        let count_blobs = || {
+            fs::read_dir(dir.path().join("blobs"))
+                .map(|prefixes| {
+                    prefixes
+                        .flatten()
+                        .map(|p| fs::read_dir(p.path()).unwrap().count())
+                        .sum::<usize>()
+                })
+                .unwrap_or(0)
+        };
+
+        let mut first = CommandExecutor::execute("echo same", &options, &i18n).unwrap();
+        let mut second = CommandExecutor::execute("echo same", &options, &i18n).unwrap();
+        second.record.timestamp = first.record.timestamp + Duration::seconds(1);
+        store.save_execution(&mut first, &i18n).unwrap();
+        store.save_execution(&mut second, &i18n).unwrap();
+        // "same\n" on stdout and an empty stderr, shared by both runs
+        assert_eq!(count_blobs(), 2);
+
+        // A record saved before blobs and sub-second ids existed, with its
+        // outputs beside the meta file
+        let mut legacy = first.record.clone();
+        legacy.timestamp = first.record.timestamp + Duration::seconds(2);
+        legacy.stdout_sha256 = None;
+        legacy.stderr_sha256 = None;
+        let record_dir = dir.path().join("records").join(&legacy.command_hash);
+        let ts = legacy.timestamp.timestamp();
+        legacy.record_id = format!("{}_{}", legacy.command_hash, ts);
+        let meta = record_dir.join(format!("meta_{}.json", ts));
+        serde_json::to_writer(fs::File::create(&meta).unwrap(), &legacy).unwrap();
+        fs::write(record_dir.join(format!("stdout_{}.txt", ts)), "old\n").unwrap();
+        fs::write(record_dir.join(format!("stderr_{}.txt", ts)), "").unwrap();
+
+        store.rebuild_index_public(&i18n).unwrap();
+        assert!(!meta.exists());
+        assert!(!record_dir.join(format!("stdout_{}.txt", ts)).exists());
+        assert_eq!(count_blobs(), 3);
+
+        let executions = store.find_executions(&legacy.command_hash, &i18n).unwrap();
+        let migrated = &executions[2].record;
+        assert_eq!(
+            migrated.stdout_sha256,
+            Some(crate::storage::hash_bytes(b"old\n"))
+        );
+        let key = migrated.file_key();
+        assert!(key.contains('-'));
+        assert!(record_dir.join(format!("meta_{}.json", key)).exists());
+        let stdouts: Vec<&str> = executions.iter().map(|e| e.stdout.as_str()).collect();
+        assert_eq!(stdouts, vec!["same\n", "same\n", "old\n"]);
+
+        // Shared blobs survive until their last record is deleted
+        store.delete_execution(&executions[2], &i18n).unwrap();
+        store.delete_execution(&executions[0], &i18n).unwrap();
+        assert_eq!(count_blobs(), 2);
+        store.delete_execution(&executions[1], &i18n).unwrap();
+        assert_eq!(count_blobs(), 0);
+    }
+
+    #[test]
+    fn large_outputs_are_compressed_and_read_back() {
+        use crate::executor::{CommandExecutor, ExecOptions};
+        let i18n = crate::i18n::I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = crate::config::Config::default();
+        config.storage.compression = Compression::Zstd;
+        config.storage.compression_threshold = 1024;
+        let store = StoreManager::new_with_config_and_base_dir(
+            config,
+            &i18n,
+            Some(dir.path().to_path_buf()),
+        )
+        .unwrap();
+        let options = ExecOptions {
+            quiet: true,
+            ..Default::default()
+        };
+
+        let mut execution =
+            CommandExecutor::execute("yes build-ok | head -n 2000", &options, &i18n).unwrap();
+        store.save_execution(&mut execution, &i18n).unwrap();
+
+        let loaded = store
+            .find_executions(&execution.record.command_hash, &i18n)
+            .unwrap()
+            .remove(0);
+        // stdout is over the threshold, the empty stderr is not
+        assert_eq!(loaded.record.stdout_codec, Compression::Zstd);
+        assert_eq!(loaded.record.stderr_codec, Compression::None);
+        let blob = loaded.stdout_path.as_ref().unwrap();
+        assert_eq!(blob.extension().and_then(|e| e.to_str()), Some("zst"));
+        assert!(fs::metadata(blob).unwrap().len() < 1024);
+        assert_eq!(loaded.stdout, "build-ok\n".repeat(2000));
+        assert_eq!(loaded.stdout_raw, execution.stdout_raw);
+    }
+
+    #[test]
+    fn long_outputs_are_stored_whole_but_viewed_in_part() {
+        use crate::executor::{CommandExecutor, ExecOptions};
+        use crate::storage::MAX_VIEW_BYTES;
+        let i18n = crate::i18n::I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = StoreManager::new_with_config_and_base_dir(
+            crate::config::Config::default(),
+            &i18n,
+            Some(dir.path().to_path_buf()),
+        )
+        .unwrap();
+        let options = ExecOptions {
+            quiet: true,
+            capture_dir: Some(store.staging_dir()),
+            max_output_bytes: 0,
+            ..Default::default()
+        };
+        let total = MAX_VIEW_BYTES + 1000;
+
+        let mut execution = CommandExecutor::execute(
+            &format!("head -c {} /dev/zero | tr '\\0' a", total),
+            &options,
+            &i18n,
+        )
+        .unwrap();
+        assert_eq!(execution.stdout_raw.len() as u64, MAX_VIEW_BYTES);
+        assert_eq!(execution.stdout_len(), total);
+        assert!(execution.stdout.contains("[... 1000 bytes not shown;"));
+        store.save_execution(&mut execution, &i18n).unwrap();
+
+        let loaded = store
+            .find_executions(&execution.record.command_hash, &i18n)
+            .unwrap()
+            .remove(0);
+        let blob = loaded.stdout_path.as_ref().unwrap();
+        assert_eq!(fs::metadata(blob).unwrap().len(), total);
+        assert_eq!(loaded.stdout_view, execution.stdout_view);
+        assert_eq!(loaded.stdout_raw, execution.stdout_raw);
+    }
+
+    #[test]
+    fn rebuild_removes_stale_staged_output() {
+        let i18n = crate::i18n::I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = StoreManager::new_with_config_and_base_dir(
+            crate::config::Config::default(),
+            &i18n,
+            Some(dir.path().to_path_buf()),
+        )
+        .unwrap();
+        let staging = store.staging_dir();
+        fs::create_dir_all(&staging).unwrap();
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(2 * 24 * 3600);
+        let stale = staging.join("stdout_crashed.part");
+        fs::File::create(&stale).unwrap().set_modified(old).unwrap();
+        let running = staging.join("stdout_running.part");
+        let held = fs::File::create(&running).unwrap();
+        held.set_modified(old).unwrap();
+        held.lock().unwrap();
+        let fresh = staging.join("stderr_fresh.part");
+        fs::write(&fresh, "").unwrap();
+
+        store.rebuild_index_public(&i18n).unwrap();
+        assert!(!stale.exists());
+        assert!(running.exists());
+        assert!(fresh.exists());
+    }
+
+    #[test]
+    fn environment_is_kept_out_of_the_index() {
+        let i18n = crate::i18n::I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = StoreManager::new_with_config_and_base_dir(
+            crate::config::Config::default(),
+            &i18n,
+            Some(dir.path().to_path_buf()),
+        )
+        .unwrap();
+        let record = CommandRecord {
+            env: Some([("DT_SECRET".to_string(), "1".to_string())].into()),
+            ..CommandRecord::for_test("env")
+        };
+        let mut execution = CommandExecution::for_test(record, "");
+        store.save_execution(&mut execution, &i18n).unwrap();
+
+        let index = fs::read_to_string(dir.path().join("index")).unwrap();
+        assert!(!index.contains("DT_SECRET"));
+        let records = store.get_all_records().unwrap();
+        let loaded = store.load_execution(&records[0], &i18n).unwrap();
+        assert_eq!(loaded.record.env, execution.record.env);
+
+        store.rebuild_index_public(&i18n).unwrap();
+        let index = fs::read_to_string(dir.path().join("index")).unwrap();
+        assert!(!index.contains("DT_SECRET"));
+    }
+
+    #[test]
+    fn taken_record_keys_are_replaced_on_save() {
+        let i18n = crate::i18n::I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = StoreManager::new_with_config_and_base_dir(
+            crate::config::Config::default(),
+            &i18n,
+            Some(dir.path().to_path_buf()),
+        )
+        .unwrap();
+        let mut first = CommandExecution::for_test(CommandRecord::for_test("echo ok"), "ok\n");
+        first.record.record_id = format!("{}_1-0", first.record.command_hash);
+        let mut second = CommandExecution::for_test(first.record.clone(), "ok\n");
+        store.save_execution(&mut first, &i18n).unwrap();
+        store.save_execution(&mut second, &i18n).unwrap();
+        assert_eq!(first.record.file_key(), "1-0");
+        assert_ne!(second.record.file_key(), "1-0");
+        assert_eq!(
+            store
+                .find_executions(&first.record.command_hash, &i18n)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn copy_path_copies_directories_recursively() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let from = dir.path().join("artifacts_1");
+        fs::create_dir_all(from.join("nested")).unwrap();
+        fs::write(from.join("0"), "a").unwrap();
+        fs::write(from.join("nested").join("1"), "b").unwrap();
+
+        let to = dir.path().join("artifacts_2");
+        copy_path(&from, &to).unwrap();
+        assert_eq!(fs::read_to_string(to.join("0")).unwrap(), "a");
+        assert_eq!(
+            fs::read_to_string(to.join("nested").join("1")).unwrap(),
+            "b"
+        );
+    }
+
+    #[test]
+    fn rebuild_skips_unreadable_records() {
+        let i18n = crate::i18n::I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = StoreManager::new_with_config_and_base_dir(
+            crate::config::Config::default(),
+            &i18n,
+            Some(dir.path().to_path_buf()),
+        )
+        .unwrap();
+        let mut execution = CommandExecution::for_test(CommandRecord::for_test("echo ok"), "ok\n");
+        store.save_execution(&mut execution, &i18n).unwrap();
+        let broken = dir.path().join("records").join("broken");
+        fs::create_dir_all(&broken).unwrap();
+        fs::write(broken.join("meta_1-0000.json"), "{").unwrap();
+        let orphan = store.blobs_dir().join("ab").join("ab00");
+        fs::create_dir_all(orphan.parent().unwrap()).unwrap();
+        fs::write(&orphan, "").unwrap();
+
+        store.rebuild_index_public(&i18n).unwrap();
+        let records = store.get_all_records().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].command, "echo ok");
+        // The broken record might refer to it, so it is kept.
+        assert!(orphan.exists());
+    }
+
+    #[test]
+    fn concurrent_saves_keep_every_record_and_distinct_short_codes() {
+        use crate::executor::{CommandExecutor, ExecOptions};
+        let i18n = crate::i18n::I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = StoreManager::new_with_config_and_base_dir(
+            crate::config::Config::default(),
+            &i18n,
+            Some(dir.path().to_path_buf()),
+        )
+        .unwrap();
+        let options = ExecOptions {
+            quiet: true,
+            ..Default::default()
+        };
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    let mut execution =
+                        CommandExecutor::execute("echo parallel", &options, &i18n).unwrap();
+                    // Assigned outside the save, as `dt run` does while it reports
+                    store
+                        .assign_short_code(&mut execution.record, &i18n)
+                        .unwrap();
+                    store.save_execution(&mut execution, &i18n).unwrap();
+                });
+            }
+        });
+
+        let records = store.get_all_records().unwrap();
+        assert_eq!(records.len(), 8);
+        let codes: HashSet<_> = records.iter().map(|r| r.short_code.clone()).collect();
+        assert_eq!(codes.len(), 8);
+        let leftovers = fs::read_dir(dir.path())
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().starts_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn corrupt_index_is_rebuilt_from_records() {
+        use crate::executor::{CommandExecutor, ExecOptions};
+        let i18n = crate::i18n::I18n::new("en");
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = StoreManager::new_with_config_and_base_dir(
+            crate::config::Config::default(),
+            &i18n,
+            Some(dir.path().to_path_buf()),
+        )
+        .unwrap();
+        let options = ExecOptions {
+            quiet: true,
+            ..Default::default()
+        };
+        let index_path = dir.path().join("index");
+        let save = |command: &str| {
+            let mut execution = CommandExecutor::execute(command, &options, &i18n).unwrap();
+            store.save_execution(&mut execution, &i18n).unwrap();
+        };
+
+        save("echo one");
+        save("echo two");
+        // A crash mid-write used to leave the index truncated
+        let content = fs::read_to_string(&index_path).unwrap();
+        fs::write(&index_path, &content[..content.len() / 2]).unwrap();
+        assert_eq!(store.get_all_records().unwrap().len(), 2);
+
+        fs::write(&index_path, "[{").unwrap();
+        save("echo three");
+        let records: Vec<CommandRecord> =
+            serde_json::from_reader(fs::File::open(&index_path).unwrap()).unwrap();
+        assert_eq!(records.len(), 3);
+    }
+
     #[test]
     fn extract_files_includes_command_when_it_is_a_path() {
         let files = extract("./Cargo.toml arg");
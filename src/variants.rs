@@ -0,0 +1,132 @@
+use crate::differ::Differ;
+use crate::i18n::I18n;
+use crate::storage::{self, CommandExecution, TrackedFile};
+use colored::*;
+
+/// Group runs by identical exit code, stdout, stderr and tracked files. Groups are returned as
+/// indexes into `executions`, most common first (ties keep first-seen order).
+pub fn group_variants(executions: &[CommandExecution]) -> Vec<Vec<usize>> {
+    let mut keys: Vec<(i32, String, String, Option<&[TrackedFile]>)> = Vec::new();
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (index, execution) in executions.iter().enumerate() {
+        let key = (
+            execution.record.exit_code,
+            execution
+                .record
+                .stdout_sha256
+                .clone()
+                .unwrap_or_else(|| storage::hash_bytes(execution.stdout_bytes())),
+            execution
+                .record
+                .stderr_sha256
+                .clone()
+                .unwrap_or_else(|| storage::hash_bytes(execution.stderr_bytes())),
+            execution
+                .record
+                .artifacts
+                .as_ref()
+                .map(|a| a.files.as_slice()),
+        );
+        match keys.iter().position(|k| *k == key) {
+            Some(group) => groups[group].push(index),
+            None => {
+                keys.push(key);
+                groups.push(vec![index]);
+            }
+        }
+    }
+    // Stable sort keeps first-seen order among equally common variants.
+    groups.sort_by_key(|group| std::cmp::Reverse(group.len()));
+    groups
+}
+
+/// Summary printed after `dt run --repeat`: distinct output variants with counts,
+/// then a diff from the most common variant to each outlier.
+pub fn summary(executions: &[CommandExecution], i18n: &I18n) -> String {
+    let groups = group_variants(executions);
+    let mut output = String::new();
+    output.push_str(&format!(
+        "{}\n",
+        i18n.t_format(
+            "repeat_summary",
+            &[&executions.len().to_string(), &groups.len().to_string()]
+        )
+        .bold()
+        .cyan()
+    ));
+    if groups.len() <= 1 {
+        output.push_str(&format!("{}\n", i18n.t("repeat_deterministic").green()));
+        return output;
+    }
+
+    for (number, group) in groups.iter().enumerate() {
+        let first = &executions[group[0]];
+        let codes: Vec<&str> = group
+            .iter()
+            .filter_map(|&i| executions[i].record.short_code.as_deref())
+            .collect();
+        output.push_str(&format!(
+            "  {}\n",
+            i18n.t_format(
+                "repeat_variant_line",
+                &[
+                    &(number + 1).to_string(),
+                    &group.len().to_string(),
+                    &first.record.exit_code.to_string(),
+                    &codes.join(", "),
+                ]
+            )
+        ));
+    }
+
+    let common = &executions[groups[0][0]];
+    for (number, group) in groups.iter().enumerate().skip(1) {
+        let outlier = &executions[group[0]];
+        output.push('\n');
+        output.push_str(&format!(
+            "{}\n",
+            i18n.t_format("repeat_variant_diff", &[&(number + 1).to_string()])
+                .bold()
+                .yellow()
+        ));
+        if let Some(diff) = Differ::diff_executions(&[common.clone(), outlier.clone()], i18n, false)
+        {
+            output.push_str(&diff);
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(exit_code: i32, stdout: &str) -> CommandExecution {
+        let record = storage::CommandRecord {
+            exit_code,
+            ..storage::CommandRecord::for_test("flaky")
+        };
+        CommandExecution::for_test(record, stdout)
+    }
+
+    #[test]
+    fn variants_are_grouped_most_common_first() {
+        let runs = vec![
+            run(0, "b\n"),
+            run(0, "a\n"),
+            run(0, "a\n"),
+            run(1, "a\n"),
+            run(0, "a\n"),
+        ];
+        assert_eq!(group_variants(&runs), vec![vec![1, 2, 4], vec![0], vec![3]]);
+    }
+
+    #[test]
+    fn identical_runs_are_reported_deterministic() {
+        let i18n = I18n::new("en");
+        let runs = vec![run(0, "same\n"), run(0, "same\n")];
+        let text = summary(&runs, &i18n);
+        assert!(text.contains("1 distinct"));
+        assert!(text.contains("deterministic"));
+    }
+}
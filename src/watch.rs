@@ -0,0 +1,426 @@
+use crate::executor::{CommandExecutor, ExecOptions, StdinSource};
+use crate::i18n::I18n;
+use crate::storage::CommandExecution;
+use crate::store_manager::StoreManager;
+use anyhow::Result;
+use colored::*;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyModifiers},
+    terminal::{self},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Terminal,
+};
+use similar::{ChangeTag, TextDiff};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Settings for `dt watch`.
+pub struct WatchOptions {
+    pub interval: Duration,
+    pub changes_only: bool,
+    pub count: Option<usize>,
+    pub interactive: bool,
+    pub use_alt_screen: bool,
+}
+
+/// Latest run as shown in the watch view.
+struct WatchState {
+    runs: usize,
+    saved: usize,
+    last: Option<CommandExecution>,
+    lines: Vec<(String, bool)>, // Output line and whether it changed since the previous run
+    last_output: Option<String>,
+    last_saved_output: Option<String>,
+    last_saved: bool,
+    error: Option<String>,
+    finished: bool,
+    scroll: u16,
+}
+
+pub struct Watcher;
+
+impl Watcher {
+    /// Re-run `command` every `interval`, saving runs into history and showing
+    /// which lines changed compared to the previous run.
+    pub fn watch(
+        command: &str,
+        exec_options: ExecOptions,
+        options: &WatchOptions,
+        store: &StoreManager,
+        i18n: &I18n,
+        lang: &str,
+    ) -> Result<()> {
+        let exec_options = ExecOptions {
+            quiet: true,
+            stdin: StdinSource::Null,
+            ..exec_options
+        };
+        let stop = Arc::new(AtomicBool::new(false));
+        let rx = Self::spawn_runner(
+            command.to_string(),
+            exec_options,
+            options.interval,
+            options.count,
+            lang.to_string(),
+            Arc::clone(&stop),
+        );
+
+        let mut state = WatchState {
+            runs: 0,
+            saved: 0,
+            last: None,
+            lines: Vec::new(),
+            last_output: None,
+            last_saved_output: None,
+            last_saved: false,
+            error: None,
+            finished: false,
+            scroll: 0,
+        };
+
+        let result = if options.interactive {
+            Self::run_interactive(command, options, store, i18n, &rx, &mut state)
+        } else {
+            Self::run_simple(command, options, store, i18n, &rx, &mut state)
+        };
+        stop.store(true, Ordering::SeqCst);
+        result
+    }
+
+    /// Background thread that executes the command on schedule and hands each run back.
+    fn spawn_runner(
+        command: String,
+        exec_options: ExecOptions,
+        interval: Duration,
+        count: Option<usize>,
+        lang: String,
+        stop: Arc<AtomicBool>,
+    ) -> mpsc::Receiver<Result<CommandExecution, String>> {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let i18n = I18n::new(&lang);
+            let mut runs = 0usize;
+            while !stop.load(Ordering::SeqCst) {
+                let started = Instant::now();
+                let result = CommandExecutor::execute(&command, &exec_options, &i18n)
+                    .map_err(|e| e.to_string());
+                if tx.send(result).is_err() {
+                    break;
+                }
+                runs += 1;
+                if count.is_some_and(|limit| runs >= limit) {
+                    break;
+                }
+                // Interval is measured start-to-start; sleep in slices so quitting is prompt.
+                while started.elapsed() < interval && !stop.load(Ordering::SeqCst) {
+                    let remaining = interval.saturating_sub(started.elapsed());
+                    thread::sleep(remaining.min(Duration::from_millis(100)));
+                }
+            }
+        });
+        rx
+    }
+
+    /// Store a finished run (unless unchanged with `--changes-only`) and refresh the view state.
+    fn record_run(
+        result: Result<CommandExecution, String>,
+        options: &WatchOptions,
+        store: &StoreManager,
+        i18n: &I18n,
+        state: &mut WatchState,
+    ) -> Result<()> {
+        let mut execution = match result {
+            Ok(execution) => execution,
+            Err(message) => {
+                state.error = Some(message);
+                return Ok(());
+            }
+        };
+        state.error = None;
+        state.runs += 1;
+
+        let output = Self::output_text(&execution);
+        let save =
+            !options.changes_only || state.last_saved_output.as_deref() != Some(output.as_str());
+        if save {
+            store.assign_short_code(&mut execution.record, i18n)?;
+            store.save_execution(&mut execution, i18n)?;
+            state.saved += 1;
+            state.last_saved_output = Some(output.clone());
+        }
+        state.last_saved = save;
+        state.lines = Self::highlight_changes(state.last_output.as_deref(), &output);
+        state.last_output = Some(output);
+        state.last = Some(execution);
+        Ok(())
+    }
+
+    /// stdout and stderr in production order (falls back to stdout then stderr).
+    fn output_text(execution: &CommandExecution) -> String {
+        execution
+            .combined_output()
+            .unwrap_or_else(|| format!("{}{}", execution.stdout, execution.stderr))
+    }
+
+    /// Split `current` into lines, flagging the ones that are new or changed compared
+    /// to `previous`. Nothing is flagged for the first run.
+    fn highlight_changes(previous: Option<&str>, current: &str) -> Vec<(String, bool)> {
+        let clean = |line: &str| line.trim_end_matches(['\n', '\r']).to_string();
+        let Some(previous) = previous else {
+            return current.lines().map(|l| (clean(l), false)).collect();
+        };
+        TextDiff::from_lines(previous, current)
+            .iter_all_changes()
+            .filter(|change| change.tag() != ChangeTag::Delete)
+            .map(|change| (clean(change.value()), change.tag() == ChangeTag::Insert))
+            .collect()
+    }
+
+    fn status_line(
+        command: &str,
+        options: &WatchOptions,
+        i18n: &I18n,
+        state: &WatchState,
+    ) -> String {
+        let interval = crate::executor::format_duration(options.interval);
+        let mut status = i18n.t_format("watch_header", &[&interval, command]);
+        if let Some(exec) = &state.last {
+            let time = exec
+                .record
+                .timestamp
+                .with_timezone(&chrono::Local)
+                .format("%H:%M:%S");
+            status.push_str(&format!(
+                "  |  #{} {}  exit={}",
+                state.runs, time, exec.record.exit_code
+            ));
+            if state.last_saved {
+                if let Some(code) = exec.record.short_code.as_deref() {
+                    status.push_str(&format!("  [code:{}]", code));
+                }
+            } else {
+                status.push_str(&format!("  {}", i18n.t("watch_unchanged_not_saved")));
+            }
+        }
+        status
+    }
+
+    fn run_simple(
+        command: &str,
+        options: &WatchOptions,
+        store: &StoreManager,
+        i18n: &I18n,
+        rx: &mpsc::Receiver<Result<CommandExecution, String>>,
+        state: &mut WatchState,
+    ) -> Result<()> {
+        while let Ok(result) = rx.recv() {
+            Self::record_run(result, options, store, i18n, state)?;
+            if let Some(error) = &state.error {
+                eprintln!("{}", error.red());
+                continue;
+            }
+            println!(
+                "{}",
+                Self::status_line(command, options, i18n, state).bold()
+            );
+            for (line, changed) in &state.lines {
+                if *changed {
+                    println!("{}", line.reversed());
+                } else {
+                    println!("{}", line);
+                }
+            }
+            println!();
+        }
+        Ok(())
+    }
+
+    fn run_interactive(
+        command: &str,
+        options: &WatchOptions,
+        store: &StoreManager,
+        i18n: &I18n,
+        rx: &mpsc::Receiver<Result<CommandExecution, String>>,
+        state: &mut WatchState,
+    ) -> Result<()> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        if options.use_alt_screen {
+            let _ = crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen);
+        }
+        let _ = crossterm::execute!(stdout, crossterm::cursor::Hide);
+        let backend = CrosstermBackend::new(stdout);
+        let mut terminal = Terminal::new(backend)?;
+        let _ = terminal.clear();
+
+        let res = Self::interactive_loop(command, options, store, i18n, rx, state, &mut terminal);
+
+        let _ = terminal.show_cursor();
+        let mut out = io::stdout();
+        if options.use_alt_screen {
+            let _ = crossterm::execute!(out, crossterm::terminal::LeaveAlternateScreen);
+        }
+        let _ = terminal::disable_raw_mode();
+        res
+    }
+
+    fn interactive_loop(
+        command: &str,
+        options: &WatchOptions,
+        store: &StoreManager,
+        i18n: &I18n,
+        rx: &mpsc::Receiver<Result<CommandExecution, String>>,
+        state: &mut WatchState,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> Result<()> {
+        let mut needs_redraw = true;
+        loop {
+            loop {
+                match rx.try_recv() {
+                    Ok(result) => {
+                        Self::record_run(result, options, store, i18n, state)?;
+                        needs_redraw = true;
+                    }
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        needs_redraw |= !state.finished;
+                        state.finished = true;
+                        break;
+                    }
+                }
+            }
+
+            if needs_redraw {
+                terminal.draw(|f| Self::render(f, command, options, i18n, state))?;
+                needs_redraw = false;
+            }
+
+            if !event::poll(Duration::from_millis(100))? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if ctrl => return Ok(()),
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        state.scroll = state.scroll.saturating_sub(1);
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        state.scroll = state.scroll.saturating_add(1);
+                    }
+                    KeyCode::PageUp => state.scroll = state.scroll.saturating_sub(10),
+                    KeyCode::PageDown => state.scroll = state.scroll.saturating_add(10),
+                    KeyCode::Home | KeyCode::Char('g') => state.scroll = 0,
+                    _ => continue,
+                }
+                needs_redraw = true;
+            } else {
+                needs_redraw = true;
+            }
+        }
+    }
+
+    fn render(
+        f: &mut ratatui::Frame,
+        command: &str,
+        options: &WatchOptions,
+        i18n: &I18n,
+        state: &mut WatchState,
+    ) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .split(f.size());
+
+        let header = Paragraph::new(Self::status_line(command, options, i18n, state))
+            .style(Style::default().add_modifier(Modifier::BOLD));
+        f.render_widget(header, rows[0]);
+
+        let body: Vec<Line> = if let Some(error) = &state.error {
+            vec![Line::from(Span::styled(
+                error.clone(),
+                Style::default().fg(Color::Red),
+            ))]
+        } else if state.last.is_none() {
+            vec![Line::from(i18n.t("watch_waiting"))]
+        } else {
+            state
+                .lines
+                .iter()
+                .map(|(line, changed)| {
+                    if *changed {
+                        Line::from(Span::styled(
+                            line.clone(),
+                            Style::default().add_modifier(Modifier::REVERSED),
+                        ))
+                    } else {
+                        Line::from(line.clone())
+                    }
+                })
+                .collect()
+        };
+        let inner_h = rows[1].height.saturating_sub(2) as usize;
+        let max_scroll = body.len().saturating_sub(inner_h) as u16;
+        state.scroll = state.scroll.min(max_scroll);
+        let para = Paragraph::new(body)
+            .block(Block::default().borders(Borders::ALL))
+            .scroll((state.scroll, 0));
+        f.render_widget(para, rows[1]);
+
+        let mut footer = i18n.t_format(
+            "watch_footer",
+            &[&state.runs.to_string(), &state.saved.to_string()],
+        );
+        if state.finished {
+            footer.push_str(&format!("  {}", i18n.t("watch_finished")));
+        }
+        f.render_widget(
+            Paragraph::new(footer).style(Style::default().fg(Color::Gray)),
+            rows[2],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_run_has_no_highlights() {
+        let lines = Watcher::highlight_changes(None, "a\nb\n");
+        assert_eq!(
+            lines,
+            vec![("a".to_string(), false), ("b".to_string(), false)]
+        );
+    }
+
+    #[test]
+    fn changed_and_added_lines_are_highlighted() {
+        let lines = Watcher::highlight_changes(
+            Some("pod-a Running\npod-b Pending\n"),
+            "pod-a Running\npod-b Running\npod-c Pending\n",
+        );
+        assert_eq!(
+            lines,
+            vec![
+                ("pod-a Running".to_string(), false),
+                ("pod-b Running".to_string(), true),
+                ("pod-c Pending".to_string(), true),
+            ]
+        );
+    }
+}
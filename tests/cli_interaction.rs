@@ -77,3 +77,532 @@ fn cli_run_ls_show_diff_smoke() {
     assert!(!stdout.trim().is_empty());
     assert!(stdout.contains("a") || stdout.contains("b"));
 }
+
+#[cfg(unix)]
+#[test]
+fn cli_run_pty_reports_terminal() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let status = dt_cmd(&home, &data_dir)
+        .args(["run", "--pty", "test -t 1 && echo tty || echo pipe"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["show", "test -t 1 && echo tty || echo pipe"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("tty"));
+    assert!(!stdout.contains("pipe"));
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_run_timeout_records_timed_out() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let started = std::time::Instant::now();
+    let status = dt_cmd(&home, &data_dir)
+        .args(["run", "--timeout", "300ms", "sleep 30"])
+        .status()
+        .unwrap();
+    // SIGTERM from the timeout is reported shell-style as 128 + 15.
+    assert_eq!(status.code(), Some(143));
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["ls", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\"termination\": \"timed_out\""));
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_run_record_and_replay_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let mut child = dt_cmd(&home, &data_dir)
+        .args(["run", "--record-stdin", "sort"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"pear\napple\n")
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["run", "--stdin-from", "last", "sort"])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("apple\npear"));
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["run", "--stdin-from", "zz", "sort"])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_replay_reemits_recorded_output() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let status = dt_cmd(&home, &data_dir)
+        .args(["run", "echo out; echo err >&2"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = dt_cmd(&home, &data_dir)
+        .args([
+            "replay",
+            "--code",
+            "a",
+            "--speed",
+            "100x",
+            "echo out; echo err >&2",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "out\n");
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "err\n");
+}
+
+#[test]
+fn cli_watch_changes_only_saves_unchanged_output_once() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let output = dt_cmd(&home, &data_dir)
+        .args([
+            "watch",
+            "--interval",
+            "100ms",
+            "--count",
+            "3",
+            "--changes-only",
+            "echo steady",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("steady\n").count(), 3);
+
+    let listing = dt_cmd(&home, &data_dir)
+        .args(["ls", "--json"])
+        .output()
+        .unwrap();
+    let records: serde_json::Value = serde_json::from_slice(&listing.stdout).unwrap();
+    assert_eq!(records.as_array().map(|a| a.len()), Some(1));
+}
+
+#[test]
+fn cli_run_repeat_records_each_run_and_summarizes() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["run", "--repeat", "2", "echo same"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2 runs, 1 distinct output variant(s)"));
+    assert!(stdout.contains("deterministic"));
+
+    let listing = dt_cmd(&home, &data_dir)
+        .args(["ls", "--json"])
+        .output()
+        .unwrap();
+    let records: serde_json::Value = serde_json::from_slice(&listing.stdout).unwrap();
+    assert_eq!(records.as_array().map(|a| a.len()), Some(2));
+}
+
+#[test]
+fn cli_run_json_and_quiet_keep_stdout_clean() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["run", "--quiet", "echo hidden"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["run", "--json", "echo hi"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["exit_code"], 0);
+    assert_eq!(summary["short_code"], "a");
+    assert_eq!(summary["stdout_bytes"], 3);
+    assert_eq!(
+        summary["stdout_sha256"],
+        "98ea6e4f216f2fb4b69fff9b3a44842c38686ca685f3f55dc48c5d3fb1107be4"
+    );
+    assert!(summary["record_id"].as_str().is_some());
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["run", "--quiet", "--repeat", "2", "echo hidden"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["run", "--json", "--repeat", "2", "echo hi"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let codes: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["short_code"].clone())
+        .collect();
+    assert_eq!(codes, ["b", "c"]);
+}
+
+#[test]
+fn cli_run_propagates_exit_status() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let status = dt_cmd(&home, &data_dir)
+        .args(["run", "exit 7"])
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(7));
+
+    let status = dt_cmd(&home, &data_dir)
+        .args(["run", "--exit-zero", "exit 7"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_run_diff_exit_code_reports_changed_output() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+    let workdir = TempDir::new().unwrap();
+    std::fs::write(workdir.path().join("v.txt"), "a\n").unwrap();
+
+    let run = |home: &TempDir, data_dir: &TempDir| {
+        dt_cmd(home, data_dir)
+            .current_dir(workdir.path())
+            .args(["run", "-d", "last", "--diff-exit-code", "cat v.txt"])
+            .status()
+            .unwrap()
+    };
+    assert!(run(&home, &data_dir).success());
+    assert!(run(&home, &data_dir).success());
+
+    std::fs::write(workdir.path().join("v.txt"), "b\n").unwrap();
+    assert_eq!(run(&home, &data_dir).code(), Some(3));
+
+    // --quiet hides the diff, but the exit status still reports it.
+    std::fs::write(workdir.path().join("v.txt"), "c\n").unwrap();
+    let output = dt_cmd(&home, &data_dir)
+        .current_dir(workdir.path())
+        .args(["run", "-q", "-d", "last", "--diff-exit-code", "cat v.txt"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(
+        output.stdout.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_rerun_uses_recorded_directory_and_diffs() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+    let workdir = TempDir::new().unwrap();
+    let elsewhere = TempDir::new().unwrap();
+    std::fs::write(workdir.path().join("v.txt"), "a\n").unwrap();
+
+    let status = dt_cmd(&home, &data_dir)
+        .current_dir(workdir.path())
+        .args(["run", "cat v.txt"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    std::fs::write(workdir.path().join("v.txt"), "b\n").unwrap();
+    let output = dt_cmd(&home, &data_dir)
+        .current_dir(elsewhere.path())
+        .args(["rerun", "a"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Re-running `cat v.txt`"));
+    assert!(stdout.contains("-a"));
+    assert!(stdout.contains("+b"));
+
+    let listing = dt_cmd(&home, &data_dir)
+        .args(["ls", "--json"])
+        .output()
+        .unwrap();
+    let records: serde_json::Value = serde_json::from_slice(&listing.stdout).unwrap();
+    assert_eq!(records.as_array().map(|a| a.len()), Some(2));
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["rerun", "zz"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_rerun_runs_the_command_as_typed() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let command = "echo 'a    b' | cat";
+    let status = dt_cmd(&home, &data_dir)
+        .args(["run", command])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["rerun", "a"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(&format!("Re-running `{}`", command)),
+        "{}",
+        stdout
+    );
+    assert!(!stdout.contains("+a b"), "{}", stdout);
+}
+
+#[test]
+fn cli_run_track_diffs_tracked_files_between_runs() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+    let workdir = TempDir::new().unwrap();
+    std::fs::write(workdir.path().join("v.txt"), "a\n").unwrap();
+
+    let run = |diff: bool| {
+        let mut cmd = dt_cmd(&home, &data_dir);
+        cmd.current_dir(workdir.path())
+            .args(["run", "--track", "out/*.txt"]);
+        if diff {
+            cmd.args(["-d", "last"]);
+        }
+        cmd.args(["mkdir -p out && cp v.txt out/copy.txt"])
+            .output()
+            .unwrap()
+    };
+    assert!(run(false).status.success());
+
+    std::fs::write(workdir.path().join("v.txt"), "b\n").unwrap();
+    let output = run(true);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Tracked files:"), "{}", stdout);
+    assert!(stdout.contains("~ out/copy.txt"));
+    assert!(stdout.contains("-a"));
+    assert!(stdout.contains("+b"));
+
+    let listing = dt_cmd(&home, &data_dir)
+        .args(["ls", "--json"])
+        .output()
+        .unwrap();
+    let records: serde_json::Value = serde_json::from_slice(&listing.stdout).unwrap();
+    let hash = records[0]["command_hash"].as_str().unwrap();
+    let saved: Vec<_> = std::fs::read_dir(data_dir.path().join("records").join(hash))
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with("artifacts_"))
+        .collect();
+    assert_eq!(saved.len(), 2);
+}
+
+#[test]
+fn cli_run_batch_summarizes_changes_against_last_run() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+    let workdir = TempDir::new().unwrap();
+    std::fs::write(workdir.path().join("v.txt"), "a\n").unwrap();
+    std::fs::write(
+        workdir.path().join("checks.toml"),
+        "[[commands]]\nname = \"steady\"\ncommand = \"echo ok\"\n\n\
+         [[commands]]\nname = \"file\"\ncommand = \"cat v.txt\"\n",
+    )
+    .unwrap();
+
+    let run = || {
+        dt_cmd(&home, &data_dir)
+            .current_dir(workdir.path())
+            .args(["run-batch", "--jobs", "2", "checks.toml"])
+            .output()
+            .unwrap()
+    };
+    let output = run();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2 command(s): 0 changed, 2 new, 0 unchanged, 0 failed"));
+
+    std::fs::write(workdir.path().join("v.txt"), "b\n").unwrap();
+    let output = run();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2 command(s): 1 changed, 0 new, 1 unchanged, 0 failed"));
+    let file_row = stdout
+        .lines()
+        .find(|l| l.starts_with("file "))
+        .expect("summary row");
+    assert!(file_row.contains("output changed"));
+}
+
+#[test]
+fn cli_runs_within_one_second_keep_separate_records() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    for _ in 0..3 {
+        let status = dt_cmd(&home, &data_dir)
+            .args(["run", "echo quick"])
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    let output = dt_cmd(&home, &data_dir)
+        .args(["ls", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_str(&String::from_utf8_lossy(&output.stdout)).unwrap();
+    let ids: std::collections::HashSet<&str> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|r| r.get("record_id").and_then(|v| v.as_str()))
+        .collect();
+    assert_eq!(ids.len(), 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_run_repeat_passes_on_failures_and_variants() {
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+
+    let status = dt_cmd(&home, &data_dir)
+        .args(["run", "--repeat", "2", "false"])
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(1));
+
+    let status = dt_cmd(&home, &data_dir)
+        .args(["run", "--repeat", "2", "--exit-zero", "false"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let status = dt_cmd(&home, &data_dir)
+        .args(["run", "--repeat", "3", "--diff-exit-code", "date +%N"])
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(3));
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_run_on_change_reruns_for_edits_made_during_a_run() {
+    use std::time::{Duration, Instant};
+    let home = TempDir::new().unwrap();
+    let data_dir = TempDir::new().unwrap();
+    let workdir = TempDir::new().unwrap();
+    std::fs::write(workdir.path().join("w.txt"), "a\n").unwrap();
+
+    let records = |home: &TempDir, data_dir: &TempDir| -> usize {
+        let listing = dt_cmd(home, data_dir)
+            .args(["ls", "--json"])
+            .output()
+            .unwrap();
+        serde_json::from_slice::<serde_json::Value>(&listing.stdout)
+            .ok()
+            .and_then(|v| v.as_array().map(|a| a.len()))
+            .unwrap_or(0)
+    };
+    let wait_for = |count: usize| {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while records(&home, &data_dir) < count {
+            assert!(Instant::now() < deadline, "expected {} runs", count);
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    };
+
+    let mut child = dt_cmd(&home, &data_dir)
+        .current_dir(workdir.path())
+        .args([
+            "run",
+            "-q",
+            "--on-change",
+            "w.txt",
+            "--debounce",
+            "100ms",
+            "cat w.txt; sleep 1",
+        ])
+        .spawn()
+        .unwrap();
+    wait_for(1);
+    // Give dt time to start watching once the first run is saved.
+    std::thread::sleep(Duration::from_millis(300));
+    std::fs::write(workdir.path().join("w.txt"), "b\n").unwrap();
+    // Edit again while the second run is still sleeping.
+    std::thread::sleep(Duration::from_millis(700));
+    std::fs::write(workdir.path().join("w.txt"), "c\n").unwrap();
+    wait_for(3);
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    // The last run saw the edit made during the second one.
+    let output = dt_cmd(&home, &data_dir)
+        .args(["show", "cat w.txt; sleep 1"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("c\n"), "{}", stdout);
+}